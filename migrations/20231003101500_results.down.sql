@@ -0,0 +1,3 @@
+DROP TABLE games.t_daily_results;
+ALTER TABLE users.basic_info DROP COLUMN rating;
+ALTER TABLE games.t_finished DROP COLUMN result;
@@ -0,0 +1,14 @@
+ALTER TABLE games.t_finished ADD COLUMN result varchar(7);
+
+ALTER TABLE users.basic_info ADD COLUMN rating int NOT NULL DEFAULT 1500;
+
+CREATE TABLE games.t_daily_results (
+  username text NOT NULL,
+  day date NOT NULL DEFAULT current_date,
+  wins int NOT NULL DEFAULT 0,
+  draws int NOT NULL DEFAULT 0,
+  losses int NOT NULL DEFAULT 0,
+  PRIMARY KEY(username, day)
+);
+
+CREATE INDEX ON games.t_daily_results(day);
@@ -0,0 +1 @@
+DROP INDEX users.basic_info_username_lower_idx;
@@ -0,0 +1,25 @@
+-- Usernames that only differ by case can not be told apart anymore. They
+-- are referenced by name from games and results, so they are not renamed
+-- here: rename all but one account of each clash by hand, along with the
+-- rows naming it, then run this migration again.
+DO $$
+DECLARE
+  clashes text;
+BEGIN
+  SELECT string_agg(names, '; ')
+  INTO clashes
+  FROM (
+    SELECT string_agg(username, ', ' ORDER BY id) as names
+    FROM users.basic_info
+    GROUP BY lower(username)
+    HAVING COUNT(1) > 1
+  ) duplicates;
+
+  IF clashes IS NOT NULL THEN
+    RAISE EXCEPTION 'Usernames differing only by case must be renamed first: %', clashes;
+  END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX basic_info_username_lower_idx
+  ON users.basic_info(lower(username));
@@ -0,0 +1,2 @@
+DROP TABLE users.refresh_token;
+ALTER TABLE users.token DROP COLUMN family;
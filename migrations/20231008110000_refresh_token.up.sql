@@ -0,0 +1,17 @@
+ALTER TABLE users.token ADD COLUMN family text;
+
+CREATE INDEX ON users.token(family);
+
+CREATE TABLE users.refresh_token (
+  token text PRIMARY KEY NOT NULL,
+  family text NOT NULL,
+  expiration timestamp NOT NULL,
+  used boolean NOT NULL DEFAULT false,
+  user_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE RESTRICT
+                  ON UPDATE CASCADE
+);
+
+CREATE INDEX ON users.refresh_token(family);
+CREATE INDEX ON users.refresh_token(user_id);
@@ -0,0 +1,15 @@
+CREATE TABLE users.blocks (
+  blocker_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  blocked_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  created_at timestamp NOT NULL DEFAULT now(),
+  PRIMARY KEY(blocker_id, blocked_id),
+  CHECK(blocker_id <> blocked_id)
+);
+
+CREATE INDEX ON users.blocks(blocked_id);
@@ -0,0 +1,2 @@
+ALTER TABLE users.basic_info DROP COLUMN friends_only_invites;
+DROP TABLE users.friends;
@@ -0,0 +1,20 @@
+CREATE TABLE users.friends (
+  requester_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  addressee_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  accepted boolean NOT NULL DEFAULT false,
+  created_at timestamp NOT NULL DEFAULT now(),
+  PRIMARY KEY(requester_id, addressee_id),
+  CHECK(requester_id <> addressee_id)
+);
+
+-- A single relation per pair, whoever asked first
+CREATE UNIQUE INDEX friends_pair_idx
+  ON users.friends(LEAST(requester_id, addressee_id), GREATEST(requester_id, addressee_id));
+
+ALTER TABLE users.basic_info ADD COLUMN friends_only_invites boolean NOT NULL DEFAULT false;
@@ -0,0 +1,10 @@
+-- Not a foreign key, games move from t_active to t_finished keeping their id
+CREATE TABLE games.t_chat (
+  id bigserial NOT NULL PRIMARY KEY,
+  id_game bigint NOT NULL,
+  author text NOT NULL,
+  body text NOT NULL,
+  sent_at timestamp NOT NULL DEFAULT now()
+);
+
+CREATE INDEX ON games.t_chat(id_game, id);
@@ -0,0 +1,10 @@
+ALTER TABLE games.t_active DROP COLUMN public;
+
+DROP VIEW games.v_pending_invites;
+ALTER TABLE games.tbl_pending_invites DROP COLUMN public;
+
+CREATE VIEW games.v_pending_invites AS (
+  SELECT inviter, invited, created_at
+  FROM games.tbl_pending_invites
+)
+WITH CASCADED CHECK OPTION;
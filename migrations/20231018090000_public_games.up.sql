@@ -0,0 +1,9 @@
+ALTER TABLE games.tbl_pending_invites ADD COLUMN public boolean NOT NULL DEFAULT false;
+
+CREATE OR REPLACE VIEW games.v_pending_invites AS (
+  SELECT inviter, invited, created_at, public
+  FROM games.tbl_pending_invites
+)
+WITH CASCADED CHECK OPTION;
+
+ALTER TABLE games.t_active ADD COLUMN public boolean NOT NULL DEFAULT false;
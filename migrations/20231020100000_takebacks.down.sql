@@ -0,0 +1,12 @@
+DROP TABLE games.t_takebacks;
+
+ALTER TABLE games.t_active DROP COLUMN takebacks;
+
+DROP VIEW games.v_pending_invites;
+ALTER TABLE games.tbl_pending_invites DROP COLUMN takebacks;
+
+CREATE VIEW games.v_pending_invites AS (
+  SELECT inviter, invited, created_at, public
+  FROM games.tbl_pending_invites
+)
+WITH CASCADED CHECK OPTION;
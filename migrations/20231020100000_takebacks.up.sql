@@ -0,0 +1,20 @@
+ALTER TABLE games.tbl_pending_invites ADD COLUMN takebacks boolean NOT NULL DEFAULT true;
+
+CREATE OR REPLACE VIEW games.v_pending_invites AS (
+  SELECT inviter, invited, created_at, public, takebacks
+  FROM games.tbl_pending_invites
+)
+WITH CASCADED CHECK OPTION;
+
+ALTER TABLE games.t_active ADD COLUMN takebacks boolean NOT NULL DEFAULT true;
+
+-- At most one pending request per game, for the move it was made on
+CREATE TABLE games.t_takebacks (
+  id_game bigint NOT NULL PRIMARY KEY
+                  REFERENCES games.t_active(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  requester text NOT NULL,
+  move_num int NOT NULL,
+  created_at timestamp NOT NULL DEFAULT now()
+);
@@ -0,0 +1,13 @@
+-- Each row is a line of alternating moves, starting with the opponent's:
+-- "if they play moves[1], reply moves[2], then if they play moves[3]..."
+CREATE TABLE games.t_premoves (
+  id bigserial NOT NULL PRIMARY KEY,
+  id_game bigint NOT NULL
+                  REFERENCES games.t_active(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE,
+  player text NOT NULL,
+  moves text[] NOT NULL CHECK(cardinality(moves) >= 2)
+);
+
+CREATE INDEX ON games.t_premoves(id_game, player);
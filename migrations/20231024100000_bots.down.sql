@@ -0,0 +1,2 @@
+DELETE FROM users.basic_info WHERE engine_depth IS NOT NULL;
+ALTER TABLE users.basic_info DROP COLUMN engine_depth;
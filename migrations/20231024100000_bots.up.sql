@@ -0,0 +1,2 @@
+-- Search depth of built-in computer opponents, NULL for people
+ALTER TABLE users.basic_info ADD COLUMN engine_depth smallint CHECK (engine_depth > 0);
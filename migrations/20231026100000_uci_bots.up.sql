@@ -0,0 +1,2 @@
+-- Bots searching with the external UCI engine instead of the built-in one
+ALTER TABLE users.basic_info ADD COLUMN engine_uci boolean NOT NULL DEFAULT false;
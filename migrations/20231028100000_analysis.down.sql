@@ -0,0 +1,3 @@
+DROP TABLE games.t_analysis;
+DROP TABLE games.t_analysis_jobs;
+ALTER TABLE games.t_finished DROP CONSTRAINT t_finished_pkey;
@@ -0,0 +1,31 @@
+ALTER TABLE games.t_finished ADD PRIMARY KEY (id);
+
+-- Games waiting to be analysed. A job started long ago without finishing
+-- belongs to a worker that died and is picked up again.
+CREATE TABLE games.t_analysis_jobs (
+  id_game bigint PRIMARY KEY NOT NULL
+             REFERENCES games.t_finished(id)
+             ON DELETE CASCADE,
+  queued_at timestamptz NOT NULL DEFAULT now(),
+  started_at timestamptz,
+  attempts int NOT NULL DEFAULT 0,
+  failed_at timestamptz
+);
+
+CREATE INDEX ON games.t_analysis_jobs(queued_at) WHERE failed_at IS NULL;
+
+CREATE TABLE games.t_analysis (
+  id_game bigint NOT NULL
+             REFERENCES games.t_finished(id)
+             ON DELETE CASCADE,
+  ply int NOT NULL,
+  san text NOT NULL,
+  white boolean NOT NULL,
+  -- Centipawns from white's side after the move, mates as +-10000
+  eval int NOT NULL,
+  best text,
+  loss int NOT NULL,
+  class text NOT NULL,
+  accuracy real NOT NULL,
+  PRIMARY KEY (id_game, ply)
+);
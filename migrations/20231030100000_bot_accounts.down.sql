@@ -0,0 +1,2 @@
+DROP TABLE users.api_tokens;
+ALTER TABLE users.basic_info DROP COLUMN bot_account;
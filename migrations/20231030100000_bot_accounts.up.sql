@@ -0,0 +1,16 @@
+-- Accounts played by third-party programs
+ALTER TABLE users.basic_info ADD COLUMN bot_account boolean NOT NULL DEFAULT false;
+
+-- Long-lived tokens of bot accounts, valid until revoked
+CREATE TABLE users.api_tokens (
+  id bigserial PRIMARY KEY NOT NULL,
+  token text UNIQUE NOT NULL,
+  name text NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  user_id bigint NOT NULL
+                  REFERENCES users.basic_info(id)
+                  ON DELETE CASCADE
+                  ON UPDATE CASCADE
+);
+
+CREATE INDEX ON users.api_tokens(user_id);
@@ -0,0 +1,5 @@
+DROP TABLE games.t_pending_imports;
+DELETE FROM games.t_finished WHERE imported;
+ALTER TABLE games.t_finished DROP COLUMN guest_b;
+ALTER TABLE games.t_finished DROP COLUMN guest_w;
+ALTER TABLE games.t_finished DROP COLUMN imported;
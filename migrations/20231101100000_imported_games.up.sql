@@ -0,0 +1,28 @@
+-- Games played elsewhere and imported from PGN, they do not count for ratings
+ALTER TABLE games.t_finished ADD COLUMN imported boolean NOT NULL DEFAULT false;
+
+-- Sides of imported games played by someone without an account, only named,
+-- so whoever registers that name later does not get the game
+ALTER TABLE games.t_finished ADD COLUMN guest_w boolean NOT NULL DEFAULT false;
+ALTER TABLE games.t_finished ADD COLUMN guest_b boolean NOT NULL DEFAULT false;
+
+-- Imported games against another member, until that member confirms them
+CREATE TABLE games.t_pending_imports (
+  id bigserial PRIMARY KEY NOT NULL,
+  importer text NOT NULL
+             REFERENCES users.basic_info(username)
+             ON DELETE CASCADE
+             ON UPDATE CASCADE,
+  opponent text NOT NULL
+             REFERENCES users.basic_info(username)
+             ON DELETE CASCADE
+             ON UPDATE CASCADE,
+  player_w text NOT NULL,
+  player_b text NOT NULL,
+  start_pos varchar(127) NOT NULL,
+  moves text NOT NULL,
+  result varchar(7) NOT NULL,
+  end_date timestamp,
+  created_at timestamp NOT NULL DEFAULT now()
+);
+CREATE INDEX ON games.t_pending_imports(opponent);
@@ -0,0 +1,14 @@
+-- Every position of a finished game, keyed by its FEN without the move
+-- counters, with the move played from it
+CREATE TABLE games.t_positions (
+  id_game bigint NOT NULL
+             REFERENCES games.t_finished(id)
+             ON DELETE CASCADE,
+  ply int NOT NULL,
+  position_key text NOT NULL,
+  -- Missing after the last move
+  next_san text,
+  PRIMARY KEY (id_game, ply)
+);
+
+CREATE INDEX ON games.t_positions(position_key);
@@ -0,0 +1,2 @@
+ALTER TABLE games.t_finished DROP COLUMN opening;
+ALTER TABLE games.t_finished DROP COLUMN eco;
@@ -0,0 +1,3 @@
+-- Opening from the bundled ECO table, missing when none matches
+ALTER TABLE games.t_finished ADD COLUMN eco varchar(3);
+ALTER TABLE games.t_finished ADD COLUMN opening text;
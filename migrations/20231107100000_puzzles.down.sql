@@ -0,0 +1,3 @@
+DROP TABLE games.t_puzzle_attempts;
+ALTER TABLE users.basic_info DROP COLUMN puzzle_rating;
+DROP TABLE games.t_puzzles;
@@ -0,0 +1,30 @@
+-- Moves are in UCI, the first one is the opponent's and sets the puzzle up
+CREATE TABLE games.t_puzzles (
+  id text PRIMARY KEY NOT NULL,
+  fen text NOT NULL,
+  moves text NOT NULL,
+  rating int NOT NULL,
+  themes text[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX ON games.t_puzzles(rating);
+
+ALTER TABLE users.basic_info ADD COLUMN puzzle_rating int NOT NULL DEFAULT 1500;
+
+-- `ply` is the index of the next move of the solution, `solved` is set
+-- once the attempt is over
+CREATE TABLE games.t_puzzle_attempts (
+  user_id bigint NOT NULL
+             REFERENCES users.basic_info(id)
+             ON DELETE CASCADE,
+  puzzle_id text NOT NULL
+             REFERENCES games.t_puzzles(id)
+             ON DELETE CASCADE,
+  ply int NOT NULL DEFAULT 1,
+  solved boolean,
+  started_at timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (user_id, puzzle_id)
+);
+
+-- A user works on one puzzle at a time
+CREATE UNIQUE INDEX ON games.t_puzzle_attempts(user_id) WHERE solved IS NULL;
@@ -0,0 +1,7 @@
+DELETE FROM games.t_active WHERE tournament_id IS NOT NULL;
+DROP INDEX games.t_active_casual_pair_idx;
+ALTER TABLE games.t_active ADD UNIQUE (player_w, player_b);
+ALTER TABLE games.t_active DROP COLUMN tournament_id;
+DROP TABLE games.t_pairings;
+DROP TABLE games.t_tournament_players;
+DROP TABLE games.t_tournaments;
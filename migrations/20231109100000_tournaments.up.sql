@@ -0,0 +1,53 @@
+CREATE TABLE games.t_tournaments (
+  id bigserial PRIMARY KEY NOT NULL,
+  name text NOT NULL,
+  organizer text NOT NULL,
+  format text NOT NULL CHECK (format IN ('round_robin', 'swiss')),
+  -- Time control, in minutes per player and seconds added per move
+  minutes int NOT NULL,
+  increment int NOT NULL,
+  -- Set when a round robin starts, one round less than its players
+  rounds int NOT NULL,
+  current_round int NOT NULL DEFAULT 0,
+  status text NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'running', 'finished')),
+  created_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- Players outlive their accounts, which leave only `[deleted]` behind
+CREATE TABLE games.t_tournament_players (
+  id bigserial PRIMARY KEY NOT NULL,
+  tournament_id bigint NOT NULL
+             REFERENCES games.t_tournaments(id)
+             ON DELETE CASCADE,
+  user_id bigint
+             REFERENCES users.basic_info(id)
+             ON DELETE SET NULL,
+  username text NOT NULL,
+  -- Rating when joining, used for seeding
+  rating int NOT NULL,
+  withdrawn boolean NOT NULL DEFAULT false,
+  UNIQUE (tournament_id, user_id)
+);
+
+-- A pairing without black is a bye. `id_game` is not a foreign key, games
+-- move from t_active to t_finished keeping their id.
+CREATE TABLE games.t_pairings (
+  tournament_id bigint NOT NULL
+             REFERENCES games.t_tournaments(id)
+             ON DELETE CASCADE,
+  round int NOT NULL,
+  board int NOT NULL,
+  white bigint NOT NULL REFERENCES games.t_tournament_players(id),
+  black bigint REFERENCES games.t_tournament_players(id),
+  id_game bigint UNIQUE,
+  result varchar(7),
+  PRIMARY KEY (tournament_id, round, board)
+);
+
+ALTER TABLE games.t_active ADD COLUMN tournament_id bigint REFERENCES games.t_tournaments(id);
+
+-- Tournament games may meet players who are already playing each other
+ALTER TABLE games.t_active DROP CONSTRAINT t_active_player_w_player_b_key;
+CREATE UNIQUE INDEX t_active_casual_pair_idx
+  ON games.t_active(player_w, player_b)
+  WHERE tournament_id IS NULL;
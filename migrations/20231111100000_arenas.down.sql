@@ -0,0 +1,9 @@
+DELETE FROM games.t_active WHERE arena_id IS NOT NULL;
+DROP INDEX games.t_active_casual_pair_idx;
+CREATE UNIQUE INDEX t_active_casual_pair_idx
+  ON games.t_active(player_w, player_b)
+  WHERE tournament_id IS NULL;
+ALTER TABLE games.t_active DROP COLUMN arena_id;
+DROP TABLE games.t_arena_games;
+DROP TABLE games.t_arena_players;
+DROP TABLE games.t_arenas;
@@ -0,0 +1,64 @@
+CREATE TABLE games.t_arenas (
+  id bigserial PRIMARY KEY NOT NULL,
+  name text NOT NULL,
+  organizer text NOT NULL,
+  -- Time control, in minutes per player and seconds added per move
+  minutes int NOT NULL,
+  increment int NOT NULL,
+  starts_at timestamptz NOT NULL,
+  ends_at timestamptz NOT NULL,
+  status text NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'running', 'finished')),
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CHECK (ends_at > starts_at)
+);
+
+CREATE TABLE games.t_arena_players (
+  id bigserial PRIMARY KEY NOT NULL,
+  arena_id bigint NOT NULL
+             REFERENCES games.t_arenas(id)
+             ON DELETE CASCADE,
+  user_id bigint
+             REFERENCES users.basic_info(id)
+             ON DELETE SET NULL,
+  username text NOT NULL,
+  rating int NOT NULL,
+  score int NOT NULL DEFAULT 0,
+  -- Games won in a row, two or more doubling the points of the next one
+  streak int NOT NULL DEFAULT 0,
+  -- Games with white minus games with black
+  colour_balance int NOT NULL DEFAULT 0,
+  -- Paused players are not paired until they join again
+  paused boolean NOT NULL DEFAULT false,
+  -- Place in the final ranking, once the arena is over
+  rank int,
+  UNIQUE (arena_id, user_id)
+);
+
+-- Like t_pairings, `id_game` follows the game from t_active to t_finished
+CREATE TABLE games.t_arena_games (
+  id_game bigint PRIMARY KEY NOT NULL,
+  arena_id bigint NOT NULL
+             REFERENCES games.t_arenas(id)
+             ON DELETE CASCADE,
+  white bigint NOT NULL REFERENCES games.t_arena_players(id),
+  black bigint NOT NULL REFERENCES games.t_arena_players(id),
+  berserk_w boolean NOT NULL DEFAULT false,
+  berserk_b boolean NOT NULL DEFAULT false,
+  -- Milliseconds left on each clock when the current turn started
+  clock_w bigint NOT NULL,
+  clock_b bigint NOT NULL,
+  turn_started_at timestamptz NOT NULL DEFAULT now(),
+  result varchar(7),
+  -- Arena points scored, left empty for games ending after the arena
+  points_w int,
+  points_b int
+);
+CREATE INDEX t_arena_games_arena_id_idx ON games.t_arena_games(arena_id);
+
+ALTER TABLE games.t_active ADD COLUMN arena_id bigint REFERENCES games.t_arenas(id);
+
+DROP INDEX games.t_active_casual_pair_idx;
+CREATE UNIQUE INDEX t_active_casual_pair_idx
+  ON games.t_active(player_w, player_b)
+  WHERE tournament_id IS NULL
+    AND arena_id IS NULL;
@@ -0,0 +1,4 @@
+DROP TABLE games.t_match_boards;
+DROP TABLE games.t_team_matches;
+DROP TABLE users.team_members;
+DROP TABLE users.teams;
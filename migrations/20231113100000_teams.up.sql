@@ -0,0 +1,54 @@
+CREATE TABLE users.teams (
+  id bigserial PRIMARY KEY NOT NULL,
+  name text NOT NULL,
+  description text NOT NULL DEFAULT '',
+  created_at timestamp NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX teams_name_lower_idx ON users.teams(lower(name));
+
+-- Members, and those invited by the team or asking to join it until the
+-- other side accepts
+CREATE TABLE users.team_members (
+  team_id bigint NOT NULL
+             REFERENCES users.teams(id)
+             ON DELETE CASCADE,
+  user_id bigint NOT NULL
+             REFERENCES users.basic_info(id)
+             ON DELETE CASCADE,
+  leader boolean NOT NULL DEFAULT false,
+  invited boolean NOT NULL,
+  accepted boolean NOT NULL DEFAULT false,
+  created_at timestamp NOT NULL DEFAULT now(),
+  PRIMARY KEY (team_id, user_id)
+);
+CREATE UNIQUE INDEX team_members_leader_idx ON users.team_members(team_id) WHERE leader;
+CREATE INDEX team_members_user_id_idx ON users.team_members(user_id);
+
+-- A disbanded team is left out of the history of its matches. Matches
+-- it had not started yet are deleted along with it.
+CREATE TABLE games.t_team_matches (
+  id bigserial PRIMARY KEY NOT NULL,
+  challenger bigint
+             REFERENCES users.teams(id)
+             ON DELETE SET NULL,
+  challenged bigint
+             REFERENCES users.teams(id)
+             ON DELETE SET NULL,
+  boards int NOT NULL,
+  -- Players of the challenger, by board, until the match is accepted
+  lineup text[] NOT NULL,
+  created_at timestamp NOT NULL DEFAULT now(),
+  accepted_at timestamp,
+  CHECK (challenger <> challenged)
+);
+
+-- Players and results are those of the game, active or finished
+CREATE TABLE games.t_match_boards (
+  match_id bigint NOT NULL
+             REFERENCES games.t_team_matches(id)
+             ON DELETE CASCADE,
+  board int NOT NULL,
+  id_game bigint NOT NULL UNIQUE,
+  challenger_white boolean NOT NULL,
+  PRIMARY KEY (match_id, board)
+);
@@ -0,0 +1,347 @@
+//! Post-game analysis. Requests are queued in `games.t_analysis_jobs` and
+//! worked through in the background, so they survive restarts.
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use axum::http::StatusCode;
+use chess::{Board, BoardStatus, ChessMove, Color};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::{
+    engine, notation,
+    uci::{self, Score},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Depth of the built-in engine, much slower than an external one
+pub(crate) const BUILTIN_DEPTH: u8 = 3;
+
+/// Centipawns standing for a forced mate
+const MATE_CP: i32 = 10_000;
+
+/// Evaluations are capped at this when measuring what a move lost, a won
+/// position stays won
+const LOSS_CAP: i32 = 1_000;
+
+/// Seconds after which a started job is taken to have died with its worker
+const STALE_SECS: f64 = 15.0 * 60.0;
+
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Wait between looks at an empty queue
+const POLL: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Class {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Class {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Class::Best => "best",
+            Class::Good => "good",
+            Class::Inaccuracy => "inaccuracy",
+            Class::Mistake => "mistake",
+            Class::Blunder => "blunder",
+        }
+    }
+}
+
+/// Judge a move by the centipawns it gave away
+pub(crate) fn classify(best: bool, loss: i32) -> Class {
+    match loss {
+        _ if best => Class::Best,
+        ..=49 => Class::Good,
+        50..=99 => Class::Inaccuracy,
+        100..=299 => Class::Mistake,
+        _ => Class::Blunder,
+    }
+}
+
+/// Centipawns of a score, mates closer to the edge the sooner they come
+pub(crate) fn centipawns(score: Score) -> i32 {
+    match score {
+        Score::Cp(cp) => cp.clamp(-MATE_CP + 1, MATE_CP - 1),
+        Score::Mate(moves) if moves > 0 => MATE_CP - moves,
+        Score::Mate(moves) => -MATE_CP - moves,
+    }
+}
+
+/// Chance of winning in percent with an evaluation of `cp`
+pub(crate) fn win_chance(cp: i32) -> f64 {
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp as f64).exp()) - 1.0)
+}
+
+/// Accuracy in percent of a move taking the mover from `before` to
+/// `after`, both from the mover's side
+pub(crate) fn accuracy(before: i32, after: i32) -> f64 {
+    let lost = (win_chance(before) - win_chance(after)).max(0.0);
+    (103.1668 * (-0.04354 * lost).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// Evaluation of a position from the side to move, with the move the
+/// engine prefers unless the game is over
+struct Evaluation {
+    best: Option<ChessMove>,
+    cp: i32,
+}
+
+async fn evaluate(
+    board: Board,
+    engines: Option<&uci::Pool>,
+    depth: u8,
+) -> Result<Evaluation, StatusCode> {
+    match board.status() {
+        BoardStatus::Checkmate => {
+            return Ok(Evaluation {
+                best: None,
+                cp: -MATE_CP,
+            })
+        }
+        BoardStatus::Stalemate => return Ok(Evaluation { best: None, cp: 0 }),
+        BoardStatus::Ongoing => {}
+    }
+
+    if let Some(engines) = engines {
+        let search = engines.search(&board, depth).await.map_err(|err| {
+            error!("Error searching with the engine {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        return Ok(Evaluation {
+            best: Some(search.best),
+            cp: centipawns(search.score.unwrap_or(Score::Cp(0))),
+        });
+    }
+
+    let (best, score) = tokio::task::spawn_blocking(move || engine::search(&board, depth))
+        .await
+        .map_err(|err| {
+            error!("Error searching for a move {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let score = if score.abs() > engine::MATE - 1_000 {
+        // Plies to mate, rounded up to moves
+        let moves = (engine::MATE - score.abs() + 1) / 2;
+        Score::Mate(moves * score.signum())
+    } else {
+        Score::Cp(score)
+    };
+
+    Ok(Evaluation {
+        best: Some(best),
+        cp: centipawns(score),
+    })
+}
+
+/// Analyse queued games forever. `depth` is the one of the external engine
+/// when there is one, else of the built-in engine.
+pub(crate) async fn work(pool: PgPool, engines: Option<Arc<uci::Pool>>, depth: u8) {
+    loop {
+        let job = match next_job(&pool).await {
+            Ok(Some(job)) => job,
+            Ok(None) | Err(_) => {
+                tokio::time::sleep(POLL).await;
+                continue;
+            }
+        };
+
+        // A job taken again after a crash may have no try left
+        let analysed = if exhausted(job.attempts - 1) {
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        } else {
+            analyse(&pool, engines.as_deref(), depth, job.id_game).await
+        };
+        if analysed.is_err() {
+            if let Err(err) = fail(&pool, &job).await {
+                error!("Error releasing analysis job {err}");
+            }
+        }
+    }
+}
+
+struct Job {
+    id_game: i64,
+    attempts: i32,
+}
+
+/// Whether a job tried `attempts` times is not tried again
+fn exhausted(attempts: i32) -> bool {
+    attempts >= MAX_ATTEMPTS
+}
+
+async fn next_job(pool: &PgPool) -> Result<Option<Job>, StatusCode> {
+    sqlx::query_as!(
+        Job,
+        "
+        UPDATE games.t_analysis_jobs
+        SET started_at = now(),
+            attempts = attempts + 1
+        WHERE id_game = (
+            SELECT id_game
+            FROM games.t_analysis_jobs
+            WHERE failed_at IS NULL
+              AND (started_at IS NULL OR started_at < now() - make_interval(secs => $1))
+            ORDER BY queued_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id_game, attempts
+        ",
+        STALE_SECS,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        error!("Error taking analysis job {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Leave a failed job for another try, or for good after too many
+async fn fail(pool: &PgPool, job: &Job) -> sqlx::Result<()> {
+    sqlx::query!(
+        "
+        UPDATE games.t_analysis_jobs
+        SET started_at = NULL,
+            failed_at = CASE WHEN $2 THEN now() END
+        WHERE id_game = $1
+        ",
+        job.id_game,
+        exhausted(job.attempts),
+    )
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+#[tracing::instrument(skip(pool, engines))]
+async fn analyse(
+    pool: &PgPool,
+    engines: Option<&uci::Pool>,
+    depth: u8,
+    id: i64,
+) -> Result<(), StatusCode> {
+    let Some(game) = sqlx::query!(
+        "
+        SELECT start_pos, moves
+        FROM games.t_finished
+        WHERE id = $1
+        ",
+        id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        // Deleted meanwhile, the job went with it
+        return Ok(());
+    };
+
+    let mut board = Board::from_str(&game.start_pos).map_err(|err| {
+        error!("Error interpreting fen from database {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let mut positions = vec![board];
+    let mut moves = Vec::new();
+    for san in game.moves.split_whitespace() {
+        let cmove = notation::parse_san(&board, san).map_err(|err| {
+            error!("Error replaying stored move {san} {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        board = board.make_move_new(cmove);
+        positions.push(board);
+        moves.push((san, cmove));
+    }
+
+    let mut evaluations = Vec::with_capacity(positions.len());
+    for position in &positions {
+        evaluations.push(evaluate(*position, engines, depth).await?);
+    }
+
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_analysis
+        WHERE id_game = $1
+        ",
+        id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error clearing old analysis {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for (ply, (san, cmove)) in moves.into_iter().enumerate() {
+        let (before, after) = (&evaluations[ply], &evaluations[ply + 1]);
+        let white = positions[ply].side_to_move() == Color::White;
+
+        // Both from the mover's side
+        let (before, after, best) = (before.cp, -after.cp, before.best);
+        let loss = (before.min(LOSS_CAP) - after.clamp(-LOSS_CAP, LOSS_CAP)).max(0);
+        let class = classify(best == Some(cmove), loss);
+
+        sqlx::query!(
+            "
+            INSERT INTO games.t_analysis(id_game, ply, san, white, eval, best, loss, class, accuracy)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ",
+            id,
+            ply as i32 + 1,
+            san,
+            white,
+            if white { after } else { -after },
+            best.map(|best| notation::san(&positions[ply], best)),
+            loss,
+            class.as_str(),
+            accuracy(before, after) as f32,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error storing analysis {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_analysis_jobs
+        WHERE id_game = $1
+        ",
+        id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error finishing analysis job {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Game analysed");
+    Ok(())
+}
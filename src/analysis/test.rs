@@ -0,0 +1,52 @@
+use super::*;
+
+#[test]
+fn classifies_by_loss() {
+    assert_eq!(classify(true, 500), Class::Best);
+    assert_eq!(classify(false, 0), Class::Good);
+    assert_eq!(classify(false, 49), Class::Good);
+    assert_eq!(classify(false, 50), Class::Inaccuracy);
+    assert_eq!(classify(false, 100), Class::Mistake);
+    assert_eq!(classify(false, 300), Class::Blunder);
+}
+
+#[test]
+fn mates_outweigh_any_material() {
+    assert_eq!(centipawns(Score::Cp(35)), 35);
+    assert!(centipawns(Score::Mate(5)) > centipawns(Score::Cp(5_000)));
+    assert!(centipawns(Score::Mate(1)) > centipawns(Score::Mate(5)));
+    assert!(centipawns(Score::Mate(-1)) < centipawns(Score::Mate(-5)));
+    assert_eq!(centipawns(Score::Mate(0)), -MATE_CP);
+}
+
+#[test]
+fn win_chance_is_symmetric() {
+    assert_eq!(win_chance(0), 50.0);
+    assert!((win_chance(300) + win_chance(-300) - 100.0).abs() < 1e-9);
+    assert!(win_chance(10_000) > 99.0);
+}
+
+#[test]
+fn accuracy_drops_with_the_loss() {
+    assert!(accuracy(50, 50) > 99.9);
+    // Improving does not score above a perfect move
+    assert_eq!(accuracy(0, 200), accuracy(0, 0));
+
+    let small = accuracy(50, 0);
+    let large = accuracy(50, -400);
+    assert!(small > large);
+    assert!((0.0..=100.0).contains(&large));
+}
+
+#[test]
+fn jobs_are_tried_up_to_the_limit() {
+    // Attempts are counted as jobs are taken
+    let tried = (1..)
+        .take_while(|attempts| !exhausted(attempts - 1))
+        .count();
+    assert_eq!(tried, MAX_ATTEMPTS as usize);
+
+    assert!(!exhausted(MAX_ATTEMPTS - 1));
+    assert!(exhausted(MAX_ATTEMPTS));
+    assert!(exhausted(MAX_ATTEMPTS + 1));
+}
@@ -0,0 +1,555 @@
+//! Arena tournaments: for a set time, players are paired again as soon as
+//! their game is over. Wins score 2 and draws 1, doubled once a player has
+//! won twice in a row, and a player who went berserk scores one more for a
+//! win.
+
+use std::{cmp::Ordering, sync::Arc, time::Duration};
+
+use axum::http::StatusCode;
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+
+use crate::{
+    game::{self, Event, Outcome},
+    hub::{GameEvent, Hub},
+    tournament,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Wait between pairings of the waiting players
+const POLL: Duration = Duration::from_secs(2);
+
+/// Wins in a row after which points are doubled
+pub(crate) const FIRE_STREAK: i32 = 2;
+
+/// Arena points of a player for a game, and their streak after it
+pub(crate) fn points(outcome: Outcome, white: bool, streak: i32, berserk: bool) -> (i32, i32) {
+    let won = match (outcome, white) {
+        (Outcome::WhiteWins, true) | (Outcome::BlackWins, false) => Some(true),
+        (Outcome::Draw, _) => None,
+        _ => Some(false),
+    };
+    let base = match won {
+        Some(true) => 2,
+        None => 1,
+        Some(false) => 0,
+    };
+    let multiplier = if streak >= FIRE_STREAK { 2 } else { 1 };
+    let bonus = i32::from(berserk && won == Some(true));
+
+    let streak = if won == Some(true) { streak + 1 } else { 0 };
+    (base * multiplier + bonus, streak)
+}
+
+/// Seconds on the clock and added per move for a player. Going berserk
+/// halves the time and gives up the increment.
+pub(crate) fn clock(minutes: i32, increment: i32, berserk: bool) -> (i32, i32) {
+    if berserk {
+        (minutes * 30, 0)
+    } else {
+        (minutes * 60, increment)
+    }
+}
+
+/// Milliseconds left on a clock after a move taking `elapsed` of them, or
+/// `None` if the time ran out before it was made
+pub(crate) fn spend(left: i64, elapsed: i64, increment: i32) -> Option<i64> {
+    let left = left - elapsed;
+    (left > 0).then(|| left + i64::from(increment) * 1000)
+}
+
+/// A player waiting for a game
+#[derive(Debug, Clone)]
+pub(crate) struct Waiting {
+    pub(crate) id: i64,
+    pub(crate) score: i32,
+    /// Games with white minus games with black
+    pub(crate) colour_balance: i32,
+    pub(crate) last_opponent: Option<i64>,
+    /// Players they blocked or were blocked by, never paired with them
+    pub(crate) blocked: Vec<i64>,
+}
+
+/// Pairs of waiting players by index, white first. Players are ranked by
+/// score and meet the next one down who is not the opponent they just
+/// played, unless nobody else is waiting. Players who blocked each other are
+/// never paired, so some may be left waiting.
+pub(crate) fn pair(waiting: &[Waiting]) -> Vec<(usize, usize)> {
+    let mut ranked: Vec<usize> = (0..waiting.len()).collect();
+    ranked.sort_by(|a, b| {
+        waiting[*b]
+            .score
+            .cmp(&waiting[*a].score)
+            .then(waiting[*a].id.cmp(&waiting[*b].id))
+    });
+
+    let mut pairs = Vec::with_capacity(ranked.len() / 2);
+    while ranked.len() >= 2 {
+        let a = ranked.remove(0);
+        let rematch = |b: &usize| {
+            waiting[a].last_opponent == Some(waiting[*b].id)
+                || waiting[*b].last_opponent == Some(waiting[a].id)
+        };
+        let blocked = |b: &usize| waiting[a].blocked.contains(&waiting[*b].id);
+        let Some(index) = ranked
+            .iter()
+            .position(|b| !blocked(b) && !rematch(b))
+            .or_else(|| ranked.iter().position(|b| !blocked(b)))
+        else {
+            continue;
+        };
+        let b = ranked.remove(index);
+
+        pairs.push(
+            match waiting[a].colour_balance.cmp(&waiting[b].colour_balance) {
+                Ordering::Greater => (b, a),
+                _ => (a, b),
+            },
+        );
+    }
+    pairs
+}
+
+/// Charge the time taken by a move to the mover's clock if game `id_game` is
+/// played in an arena, refusing the move once that clock ran out
+pub(crate) async fn tick(
+    trx: &mut PgConnection,
+    id_game: i64,
+    white: bool,
+) -> Result<(), StatusCode> {
+    let game = sqlx::query!(
+        r#"
+        SELECT g.clock_w, g.clock_b, g.berserk_w, g.berserk_b, a.minutes, a.increment,
+            (EXTRACT(EPOCH FROM now() - g.turn_started_at) * 1000)::bigint as "elapsed!"
+        FROM games.t_arena_games g
+            JOIN games.t_arenas a ON a.id = g.arena_id
+        WHERE g.id_game = $1
+        FOR UPDATE OF g
+        "#,
+        id_game,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena clocks {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let Some(game) = game else {
+        return Ok(());
+    };
+
+    let (left, berserk) = if white {
+        (game.clock_w, game.berserk_w)
+    } else {
+        (game.clock_b, game.berserk_b)
+    };
+    let (_, increment) = clock(game.minutes, game.increment, berserk);
+    let left = spend(left, game.elapsed, increment).ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arena_games
+        SET clock_w = CASE WHEN $2 THEN $3 ELSE clock_w END,
+            clock_b = CASE WHEN $2 THEN clock_b ELSE $3 END,
+            turn_started_at = now()
+        WHERE id_game = $1
+        ",
+        id_game,
+        white,
+        left,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error updating arena clocks {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+/// Score game `id_game` if it was played in an arena still running
+pub(crate) async fn record(
+    trx: &mut PgConnection,
+    id_game: i64,
+    outcome: Outcome,
+) -> Result<(), StatusCode> {
+    let game = sqlx::query!(
+        r#"
+        UPDATE games.t_arena_games g
+        SET result = $2
+        FROM games.t_arenas a
+        WHERE a.id = g.arena_id
+          AND g.id_game = $1
+        RETURNING g.arena_id, g.white, g.black, g.berserk_w, g.berserk_b,
+            a.status = 'running' AND a.ends_at > now() as "running!"
+        "#,
+        id_game,
+        outcome.as_pgn(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error recording arena result {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let Some(game) = game.filter(|game| game.running) else {
+        return Ok(());
+    };
+
+    let mut scored = Vec::with_capacity(2);
+    for (player, white, berserk) in [
+        (game.white, true, game.berserk_w),
+        (game.black, false, game.berserk_b),
+    ] {
+        let streak = sqlx::query_scalar!(
+            "
+            SELECT streak
+            FROM games.t_arena_players
+            WHERE id = $1
+            FOR UPDATE
+            ",
+            player,
+        )
+        .fetch_one(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error getting arena streak {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        let (points, streak) = points(outcome, white, streak, berserk);
+        sqlx::query!(
+            "
+            UPDATE games.t_arena_players
+            SET score = score + $2,
+                streak = $3
+            WHERE id = $1
+            ",
+            player,
+            points,
+            streak,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error updating arena score {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        scored.push(points);
+    }
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arena_games
+        SET points_w = $2,
+            points_b = $3
+        WHERE id_game = $1
+        ",
+        id_game,
+        scored[0],
+        scored[1],
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error storing arena points {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+/// Start arenas on time, pair their waiting players, end games lost on time
+/// and rank players once over
+pub(crate) async fn direct(pool: PgPool, hub: Arc<Hub>) {
+    loop {
+        tokio::time::sleep(POLL).await;
+
+        // Errors are logged, everything is tried again on the next poll
+        let _ = flag(&pool, &hub).await;
+        let _ = end(&pool).await;
+
+        let started = sqlx::query!(
+            "
+            UPDATE games.t_arenas
+            SET status = 'running'
+            WHERE status = 'open'
+              AND starts_at <= now()
+            ",
+        )
+        .execute(&pool)
+        .await;
+        if let Err(err) = started {
+            error!("Error starting arenas {err}");
+        }
+
+        let running = sqlx::query_scalar!(
+            "
+            SELECT id
+            FROM games.t_arenas
+            WHERE status = 'running'
+            ",
+        )
+        .fetch_all(&pool)
+        .await;
+        match running {
+            Ok(running) => {
+                for id in running {
+                    let _ = pair_waiting(&pool, &hub, id).await;
+                }
+            }
+            Err(err) => error!("Error getting running arenas {err}"),
+        }
+    }
+}
+
+/// Finish the arena games whose side to move ran out of time. Games with a
+/// move being played are left for the next poll.
+async fn flag(pool: &PgPool, hub: &Hub) -> Result<(), StatusCode> {
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let flagged = sqlx::query!(
+        r#"
+        SELECT ac.id, split_part(ac.fen, ' ', 2) = 'w' as "white!"
+        FROM games.t_active ac
+            JOIN games.t_arena_games g ON g.id_game = ac.id
+        WHERE CASE WHEN split_part(ac.fen, ' ', 2) = 'w' THEN g.clock_w ELSE g.clock_b END
+            <= EXTRACT(EPOCH FROM now() - g.turn_started_at) * 1000
+        FOR UPDATE OF ac, g
+        SKIP LOCKED
+        "#,
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting games out of time {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut finished = Vec::with_capacity(flagged.len());
+    for game in flagged {
+        let outcome = if game.white {
+            Outcome::BlackWins
+        } else {
+            Outcome::WhiteWins
+        };
+        game::finish(&mut trx, game.id, outcome).await?;
+        finished.push((game.id, outcome));
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for (id, outcome) in finished {
+        hub.publish(
+            id,
+            GameEvent::Finished {
+                result: outcome.as_pgn(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Finish the arenas whose time is up, ranking their players by score.
+/// Games still being played no longer count.
+async fn end(pool: &PgPool) -> Result<(), StatusCode> {
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let ended = sqlx::query_scalar!(
+        "
+        UPDATE games.t_arenas
+        SET status = 'finished'
+        WHERE status <> 'finished'
+          AND ends_at <= now()
+        RETURNING id
+        ",
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error ending arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arena_players pl
+        SET rank = ranked.place
+        FROM (
+            SELECT id, ROW_NUMBER() OVER (
+                PARTITION BY arena_id
+                ORDER BY score DESC, rating DESC, id
+            ) as place
+            FROM games.t_arena_players
+            WHERE arena_id = ANY($1)
+        ) ranked
+        WHERE pl.id = ranked.id
+        ",
+        &ended,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error ranking arena players {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for id in ended {
+        info!("Arena {id} finished");
+    }
+    Ok(())
+}
+
+/// Start games between the players of arena `id` not playing
+async fn pair_waiting(pool: &PgPool, hub: &Hub, id: i64) -> Result<(), StatusCode> {
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Locked so players cannot pause while being paired
+    let arena = sqlx::query!(
+        r#"
+        SELECT status = 'running' AND ends_at > now() as "running!", minutes, increment
+        FROM games.t_arenas
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id,
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !arena.running {
+        return Ok(());
+    }
+    let (seconds, _) = clock(arena.minutes, arena.increment, false);
+    let time = i64::from(seconds) * 1000;
+
+    let players = sqlx::query!(
+        r#"
+        SELECT pl.id, pl.username, pl.score, pl.colour_balance,
+            (
+                SELECT CASE WHEN g.white = pl.id THEN g.black ELSE g.white END
+                FROM games.t_arena_games g
+                WHERE g.white = pl.id
+                   OR g.black = pl.id
+                ORDER BY g.id_game DESC
+                LIMIT 1
+            ) as last_opponent,
+            ARRAY(
+                SELECT other.id
+                FROM games.t_arena_players other
+                    JOIN users.blocks bl
+                      ON (bl.blocker_id = pl.user_id AND bl.blocked_id = other.user_id)
+                      OR (bl.blocker_id = other.user_id AND bl.blocked_id = pl.user_id)
+                WHERE other.arena_id = pl.arena_id
+            ) as "blocked!"
+        FROM games.t_arena_players pl
+        WHERE pl.arena_id = $1
+          AND NOT pl.paused
+          AND NOT EXISTS (
+            SELECT
+            FROM games.t_arena_games g
+            WHERE (g.white = pl.id OR g.black = pl.id)
+              AND g.result IS NULL
+          )
+        "#,
+        id,
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting waiting players {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let waiting: Vec<Waiting> = players
+        .iter()
+        .map(|player| Waiting {
+            id: player.id,
+            score: player.score,
+            colour_balance: player.colour_balance,
+            last_opponent: player.last_opponent,
+            blocked: player.blocked.clone(),
+        })
+        .collect();
+
+    let mut starts = Vec::new();
+    for (white, black) in pair(&waiting) {
+        let (white, black) = (&players[white], &players[black]);
+        let (id_game, start) = game::create(
+            &mut trx,
+            &white.username,
+            &black.username,
+            true,
+            false,
+            Event::Arena(id),
+        )
+        .await?;
+        starts.push(start);
+
+        sqlx::query!(
+            "
+            INSERT INTO games.t_arena_games(id_game, arena_id, white, black, clock_w, clock_b)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ",
+            id_game,
+            id,
+            white.id,
+            black.id,
+            time,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error inserting arena game {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        sqlx::query!(
+            "
+            UPDATE games.t_arena_players
+            SET colour_balance = colour_balance + CASE WHEN id = $1 THEN 1 ELSE -1 END
+            WHERE id = $1
+               OR id = $2
+            ",
+            white.id,
+            black.id,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error updating colours {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tournament::announce(hub, starts);
+    Ok(())
+}
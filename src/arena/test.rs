@@ -0,0 +1,106 @@
+use super::*;
+
+fn waiting(id: i64, score: i32, colour_balance: i32, last_opponent: Option<i64>) -> Waiting {
+    Waiting {
+        id,
+        score,
+        colour_balance,
+        last_opponent,
+        blocked: Vec::new(),
+    }
+}
+
+#[test]
+fn scores_wins_and_draws() {
+    assert_eq!(points(Outcome::WhiteWins, true, 0, false), (2, 1));
+    assert_eq!(points(Outcome::WhiteWins, false, 1, false), (0, 0));
+    assert_eq!(points(Outcome::Draw, true, 1, false), (1, 0));
+    assert_eq!(points(Outcome::BlackWins, false, 0, false), (2, 1));
+}
+
+#[test]
+fn doubles_points_on_a_streak() {
+    assert_eq!(points(Outcome::WhiteWins, true, 1, false), (2, 2));
+    assert_eq!(points(Outcome::WhiteWins, true, 2, false), (4, 3));
+    assert_eq!(points(Outcome::Draw, false, 3, false), (2, 0));
+    assert_eq!(points(Outcome::WhiteWins, false, 3, false), (0, 0));
+}
+
+#[test]
+fn rewards_berserk_wins_only() {
+    assert_eq!(points(Outcome::BlackWins, false, 0, true), (3, 1));
+    assert_eq!(points(Outcome::BlackWins, false, 2, true), (5, 3));
+    assert_eq!(points(Outcome::Draw, false, 0, true), (1, 0));
+    assert_eq!(points(Outcome::WhiteWins, false, 0, true), (0, 0));
+}
+
+#[test]
+fn berserk_halves_the_clock() {
+    assert_eq!(clock(3, 2, false), (180, 2));
+    assert_eq!(clock(3, 2, true), (90, 0));
+}
+
+#[test]
+fn pairs_neighbours_by_score() {
+    let players = [
+        waiting(1, 0, 0, None),
+        waiting(2, 6, 0, None),
+        waiting(3, 2, 0, None),
+        waiting(4, 5, 0, None),
+        waiting(5, 1, 0, None),
+    ];
+
+    assert_eq!(pair(&players), vec![(1, 3), (2, 4)]);
+}
+
+#[test]
+fn avoids_immediate_rematches() {
+    let players = [
+        waiting(1, 4, 0, Some(2)),
+        waiting(2, 4, 0, Some(1)),
+        waiting(3, 2, 0, Some(4)),
+        waiting(4, 2, 0, Some(3)),
+    ];
+    assert_eq!(pair(&players), vec![(0, 2), (1, 3)]);
+
+    // Unless they are the only ones waiting
+    assert_eq!(pair(&players[..2]), vec![(0, 1)]);
+}
+
+#[test]
+fn never_pairs_players_who_blocked_each_other() {
+    let mut players = [
+        waiting(1, 4, 0, Some(3)),
+        waiting(2, 4, 0, None),
+        waiting(3, 2, 0, Some(1)),
+    ];
+    players[0].blocked = vec![2];
+    players[1].blocked = vec![1];
+    // Even a rematch is better than a blocked opponent
+    assert_eq!(pair(&players), vec![(0, 2)]);
+
+    // Left waiting rather than paired with them
+    assert_eq!(pair(&players[..2]), vec![]);
+}
+
+#[test]
+fn gives_white_to_whoever_had_black_more() {
+    let players = [waiting(1, 4, 1, None), waiting(2, 2, -1, None)];
+    assert_eq!(pair(&players), vec![(1, 0)]);
+
+    let players = [waiting(1, 4, 0, None), waiting(2, 2, 0, None)];
+    assert_eq!(pair(&players), vec![(0, 1)]);
+}
+
+#[test]
+fn spends_the_time_taken_and_adds_the_increment() {
+    assert_eq!(spend(60_000, 4_500, 2), Some(57_500));
+    assert_eq!(spend(60_000, 4_500, 0), Some(55_500));
+    assert_eq!(spend(1_000, 999, 0), Some(1));
+}
+
+#[test]
+fn refuses_moves_once_out_of_time() {
+    assert_eq!(spend(1_000, 1_000, 2), None);
+    assert_eq!(spend(1_000, 3_000, 2), None);
+}
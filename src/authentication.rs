@@ -1,14 +1,21 @@
+use std::sync::Arc;
+
 use axum::{
     extract::State,
     http::{Request, StatusCode},
     middleware::Next,
     response::Response,
 };
+use chrono::{DateTime, Utc};
 use rand::distributions::{Alphanumeric, DistString};
-use sqlx::PgPool;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
 use tracing::error;
 
+use crate::{config::SessionLifetimes, presence::Presence};
+
 #[cfg(test)]
+#[allow(unknown_lints, clippy::needless_as_bytes)]
 mod test;
 
 pub(crate) fn is_pass_equivalent(a: &str, b: &str) -> bool {
@@ -19,9 +26,121 @@ pub(crate) fn generate_token() -> String {
     Alphanumeric.sample_string(&mut rand::thread_rng(), 64)
 }
 
-#[tracing::instrument]
+/// Access and refresh token pair handed to clients
+#[derive(Serialize, Debug)]
+pub(crate) struct Session {
+    token: String,
+    expires_at: DateTime<Utc>,
+    refresh_token: String,
+    refresh_expires_at: DateTime<Utc>,
+    user_id: i64,
+    username: String,
+}
+
+/// Issue a new token pair. Tokens rotated from the same login share a
+/// `family`, so the whole chain can be revoked at once.
+pub(crate) async fn issue_session(
+    conn: &mut PgConnection,
+    lifetimes: &SessionLifetimes,
+    user_id: i64,
+    username: &str,
+    family: &str,
+) -> Result<Session, StatusCode> {
+    let token = generate_token();
+    let refresh_token = generate_token();
+
+    let expires_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO users.token(token, user_id, family, expiration)
+        VALUES($1, $2, $3, now() + $4 * interval '1 second')
+        RETURNING expiration::timestamptz as "expiration!"
+        "#,
+        &token,
+        user_id,
+        family,
+        lifetimes.access_secs as f64,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error storing new token {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let refresh_expires_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO users.refresh_token(token, user_id, family, expiration)
+        VALUES($1, $2, $3, now() + $4 * interval '1 second')
+        RETURNING expiration::timestamptz as "expiration!"
+        "#,
+        &refresh_token,
+        user_id,
+        family,
+        lifetimes.refresh_secs as f64,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error storing new refresh token {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Session {
+        token,
+        expires_at,
+        refresh_token,
+        refresh_expires_at,
+        user_id,
+        username: username.to_string(),
+    })
+}
+
+/// Remove every token, access or refresh, issued to a user
+pub(crate) async fn revoke_all(conn: &mut PgConnection, user_id: i64) -> Result<(), StatusCode> {
+    sqlx::query!(
+        "
+        DELETE FROM users.token
+        WHERE user_id = $1
+        ",
+        user_id,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error revoking tokens {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM users.refresh_token
+        WHERE user_id = $1
+        ",
+        user_id,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error revoking refresh tokens {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+/// Token of an `Authorization` header value, either `Bearer <token>` or the
+/// bare token
+fn bearer_token(value: &str) -> &str {
+    match value.split_once(' ') {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => token.trim(),
+        _ => value.trim(),
+    }
+}
+
+#[tracing::instrument(skip(presence))]
 pub(crate) async fn auth<B: std::fmt::Debug>(
     State(postgres): State<PgPool>,
+    State(presence): State<Arc<Presence>>,
     mut req: Request<B>,
     next: Next<B>,
 ) -> Result<Response, StatusCode> {
@@ -30,17 +149,26 @@ pub(crate) async fn auth<B: std::fmt::Debug>(
         .get("Authorization")
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    let s = s.to_str().map_err(|_| StatusCode::BAD_REQUEST)?.to_string();
+    let s = bearer_token(s.to_str().map_err(|_| StatusCode::BAD_REQUEST)?).to_string();
 
+    // Session tokens, or API tokens of bot accounts
     let res = sqlx::query_as!(
         LoggedUser,
-        "
-        SELECT id, username
+        r#"
+        SELECT id as "id!", username as "username!"
         FROM users.token t
             JOIN users.basic_info u ON u.id = t.user_id
         WHERE token = $1
           AND expiration > now()
-        ",
+
+        UNION ALL
+
+        SELECT u.id, u.username
+        FROM users.api_tokens a
+            JOIN users.basic_info u ON u.id = a.user_id
+        WHERE a.token = $1
+          AND u.bot_account
+        "#,
         s,
     )
     .fetch_optional(&postgres)
@@ -51,6 +179,7 @@ pub(crate) async fn auth<B: std::fmt::Debug>(
     })?
     .ok_or(StatusCode::UNAUTHORIZED)?;
 
+    presence.seen(res.id);
     req.extensions_mut().insert(res);
 
     Ok(next.run(req).await)
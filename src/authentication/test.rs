@@ -15,3 +15,10 @@ fn token_generation() {
         map.insert(s);
     }
 }
+
+#[test]
+fn authorization_header() {
+    assert_eq!(bearer_token("Bearer abc"), "abc");
+    assert_eq!(bearer_token("bearer  abc "), "abc");
+    assert_eq!(bearer_token("abc"), "abc");
+}
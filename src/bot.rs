@@ -0,0 +1,286 @@
+//! Computer opponents. They are users with an `engine_depth`, who can not
+//! log in and answer moves on their own, with the built-in engine or the
+//! external one when `engine_uci` is set.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::http::StatusCode;
+use chess::{Board, BoardStatus, Color};
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, warn};
+
+use crate::{
+    engine,
+    game::{self, Played},
+    hub::Hub,
+    notation, uci,
+};
+
+/// Wait between looks for games left on a bot's turn
+const RETRY: Duration = Duration::from_secs(30);
+
+/// Games a bot is searching a move for
+static THINKING: Mutex<BTreeSet<i64>> = Mutex::new(BTreeSet::new());
+
+/// Create the configured bots, or update existing ones
+pub(crate) async fn register(
+    pool: &PgPool,
+    bots: &HashMap<String, u8>,
+    uci: bool,
+) -> sqlx::Result<()> {
+    for (username, depth) in bots {
+        let updated = sqlx::query!(
+            "
+            UPDATE users.basic_info
+            SET engine_depth = $2,
+                engine_uci = $3
+            WHERE username = $1
+              AND engine_depth IS NOT NULL
+            ",
+            username,
+            *depth as i16,
+            uci,
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if updated > 0 {
+            continue;
+        }
+
+        // Bots have no password anybody could log in with
+        let inserted = sqlx::query!(
+            "
+            INSERT INTO users.basic_info(username, password, engine_depth, engine_uci)
+            VALUES ($1, '', $2, $3)
+            ON CONFLICT DO NOTHING
+            ",
+            username,
+            *depth as i16,
+            uci,
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            warn!("Bot {username} not created, a person already has that name");
+        }
+    }
+
+    Ok(())
+}
+
+/// What a bot plays with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Engine {
+    BuiltIn,
+    Uci,
+}
+
+/// Engine of `username` if it is a bot
+pub(crate) async fn engine(
+    conn: &mut PgConnection,
+    username: &str,
+) -> Result<Option<Engine>, StatusCode> {
+    let engine = sqlx::query!(
+        "
+        SELECT engine_depth, engine_uci
+        FROM users.basic_info
+        WHERE username = $1
+        ",
+        username,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting engine depth {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(engine
+        .filter(|engine| engine.engine_depth.is_some())
+        .map(|engine| {
+            if engine.engine_uci {
+                Engine::Uci
+            } else {
+                Engine::BuiltIn
+            }
+        }))
+}
+
+/// Answer in every game left on a bot's turn, by a restart or a failed
+/// search, now and then for as long as the server runs
+pub(crate) async fn resume(pool: PgPool, hub: Arc<Hub>, engines: Option<Arc<uci::Pool>>) {
+    loop {
+        let due = sqlx::query_scalar!(
+            "
+            SELECT ac.id
+            FROM games.t_active ac
+                JOIN users.basic_info u ON u.username = CASE
+                    WHEN split_part(ac.fen, ' ', 2) = 'w' THEN ac.player_w
+                    ELSE ac.player_b
+                END
+            WHERE u.engine_depth IS NOT NULL
+              AND (NOT u.engine_uci OR $1)
+            ",
+            engines.is_some(),
+        )
+        .fetch_all(&pool)
+        .await;
+        match due {
+            Ok(due) => {
+                for id in due {
+                    tokio::spawn(reply(pool.clone(), hub.clone(), engines.clone(), id));
+                }
+            }
+            Err(err) => error!("Error getting games on a bot's turn {err}"),
+        }
+
+        tokio::time::sleep(RETRY).await;
+    }
+}
+
+/// Marks a game as being searched until dropped
+struct Thinking(i64);
+
+impl Thinking {
+    /// None when the game is already being searched
+    fn start(id: i64) -> Option<Self> {
+        let mut thinking = THINKING.lock().expect("bot mutex poisoned");
+        thinking.insert(id).then_some(Self(id))
+    }
+}
+
+impl Drop for Thinking {
+    fn drop(&mut self) {
+        THINKING.lock().expect("bot mutex poisoned").remove(&self.0);
+    }
+}
+
+/// Play for the bot in game `id` for as long as it is its turn. Meant to be
+/// spawned once a move is committed, so searching does not hold up the
+/// request.
+#[tracing::instrument(skip(pool, hub, engines))]
+pub(crate) async fn reply(pool: PgPool, hub: Arc<Hub>, engines: Option<Arc<uci::Pool>>, id: i64) {
+    // A game left over is searched once, not again for every look
+    let Some(_thinking) = Thinking::start(id) else {
+        return;
+    };
+
+    loop {
+        let played = match turn(&pool, engines.as_deref(), id).await {
+            Ok(played) => played,
+            Err(status) => {
+                error!("Bot could not move: {status}");
+                return;
+            }
+        };
+
+        for played in &played {
+            for event in played.events() {
+                hub.publish(played.id, event);
+            }
+        }
+
+        // A premove answering the bot makes it its turn again
+        match played.as_slice() {
+            [_, answer] if answer.outcome.is_none() => continue,
+            _ => return,
+        }
+    }
+}
+
+/// Moves stored for the bot and for the opponent's premove answering it
+async fn turn(
+    pool: &PgPool,
+    engines: Option<&uci::Pool>,
+    id: i64,
+) -> Result<Vec<Played>, StatusCode> {
+    let Some(bot) = sqlx::query!(
+        r#"
+        SELECT
+            ac.fen,
+            u.username,
+            u.engine_depth as "engine_depth!",
+            u.engine_uci,
+            u.username = ac.player_w as "white!"
+        FROM games.t_active ac
+            JOIN users.basic_info u ON u.username IN (ac.player_w, ac.player_b)
+        WHERE ac.id = $1
+          AND u.engine_depth IS NOT NULL
+        "#,
+        id,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let board = Board::from_str(&bot.fen).map_err(|err| {
+        error!("Error interpreting fen from database {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let color = if bot.white {
+        Color::White
+    } else {
+        Color::Black
+    };
+    if board.side_to_move() != color {
+        return Ok(Vec::new());
+    }
+
+    let depth = bot.engine_depth as u8;
+    let cmove = if bot.engine_uci {
+        // The engine is gone from the configuration
+        let engines = engines.ok_or(StatusCode::SERVICE_UNAVAILABLE)?;
+        if board.status() != BoardStatus::Ongoing {
+            return Ok(Vec::new());
+        }
+        engines
+            .search(&board, depth)
+            .await
+            .map_err(|err| {
+                error!("Error searching with the engine {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .best
+    } else {
+        let best = tokio::task::spawn_blocking(move || engine::best_move(&board, depth))
+            .await
+            .map_err(|err| {
+                error!("Error searching for a move {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        match best {
+            Some(best) => best,
+            None => return Ok(Vec::new()),
+        }
+    };
+
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Should the game have changed while searching, the move is refused here
+    let played =
+        game::play_move(&mut trx, id, &bot.username, &notation::san(&board, cmove)).await?;
+    let answer = game::premove::respond(&mut trx, &played).await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(std::iter::once(played).chain(answer).collect())
+}
@@ -0,0 +1,319 @@
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+
+use axum::extract::FromRef;
+use sqlx::PgPool;
+
+use crate::{
+    hub::Hub,
+    presence::Presence,
+    throttle::{AttemptStore, TokenBuckets},
+    uci,
+    validation::DELETED_USERNAME,
+};
+
+/// Runtime settings, read once from the environment at startup
+#[derive(Debug)]
+pub(crate) struct Config {
+    pub(crate) registration: RegistrationRules,
+    pub(crate) sessions: SessionLifetimes,
+    pub(crate) login_throttle: ThrottlePolicy,
+    pub(crate) rate_limits: RateLimits,
+    /// Seconds without requests after which a user is shown offline
+    pub(crate) presence_timeout_secs: u64,
+    /// Maximum characters of a chat message
+    pub(crate) chat_max_len: usize,
+    /// Whether public games can be watched without logging in
+    pub(crate) anonymous_spectators: bool,
+    /// Built-in computer opponents, by username, with their search depth
+    pub(crate) bots: HashMap<String, u8>,
+    /// External engine, if one is installed
+    pub(crate) uci: Option<UciEngine>,
+    /// Games analysed at once
+    pub(crate) analysis_workers: usize,
+    /// CSV file puzzles are imported from at startup
+    pub(crate) puzzles_csv: Option<String>,
+}
+
+impl Config {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            registration: RegistrationRules {
+                username_min_len: var("USERNAME_MIN_LEN", 3),
+                username_max_len: var("USERNAME_MAX_LEN", 32),
+                username_charset: var(
+                    "USERNAME_CHARSET",
+                    Charset::from_str("a-zA-Z0-9_-").expect("default charset is valid"),
+                ),
+                password_min_len: var("PASSWORD_MIN_LEN", 8),
+                reserved_usernames: list(
+                    "RESERVED_USERNAMES",
+                    &["admin", "administrator", "root", "system", DELETED_USERNAME],
+                ),
+            },
+            sessions: SessionLifetimes {
+                access_secs: var("ACCESS_TOKEN_LIFETIME", 15 * 60),
+                refresh_secs: var("REFRESH_TOKEN_LIFETIME", 30 * 24 * 60 * 60),
+            },
+            login_throttle: ThrottlePolicy {
+                base_delay_secs: var("LOGIN_BACKOFF_BASE", 1),
+                max_delay_secs: var("LOGIN_BACKOFF_MAX", 60),
+                lockout_after: var("LOGIN_LOCKOUT_AFTER", 10),
+                lockout_secs: var("LOGIN_LOCKOUT_DURATION", 15 * 60),
+            },
+            rate_limits: RateLimits {
+                default: var("RATE_LIMIT_DEFAULT", Budget::new(60, 60)),
+                routes: pairs(
+                    "RATE_LIMITS",
+                    &[
+                        ("/invite", Budget::new(10, 600)),
+                        ("/send_message", Budget::new(5, 10)),
+                    ],
+                ),
+            },
+            presence_timeout_secs: var("PRESENCE_TIMEOUT", 5 * 60),
+            chat_max_len: var("CHAT_MAX_LEN", 500),
+            anonymous_spectators: var("ANONYMOUS_SPECTATORS", false),
+            bots: pairs(
+                "BOTS",
+                &[("bot-easy", 1), ("bot-medium", 2), ("bot-hard", 4)],
+            ),
+            uci: std::env::var("UCI_ENGINE").ok().map(|path| UciEngine {
+                path,
+                pool_size: var("UCI_POOL_SIZE", 2),
+                timeout_secs: var("UCI_TIMEOUT", 30),
+                analysis_depth: var("ANALYSIS_DEPTH", 12),
+                bots: pairs("UCI_BOTS", &[]),
+            }),
+            analysis_workers: var("ANALYSIS_WORKERS", 1),
+            puzzles_csv: std::env::var("PUZZLES_CSV").ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RegistrationRules {
+    pub(crate) username_min_len: usize,
+    pub(crate) username_max_len: usize,
+    pub(crate) username_charset: Charset,
+    pub(crate) password_min_len: usize,
+    /// Compared case-insensitively
+    pub(crate) reserved_usernames: Vec<String>,
+}
+
+/// UCI engine binary the server runs as a pool of processes
+#[derive(Debug)]
+pub(crate) struct UciEngine {
+    pub(crate) path: String,
+    /// Processes running at most at once
+    pub(crate) pool_size: usize,
+    /// Seconds a search may take before the process is killed
+    pub(crate) timeout_secs: u64,
+    /// Depth positions are analysed to, the built-in engine uses its own
+    pub(crate) analysis_depth: u8,
+    /// Computer opponents playing with this engine, with their search depth
+    pub(crate) bots: HashMap<String, u8>,
+}
+
+/// Lifetimes in seconds of the tokens issued on login
+#[derive(Debug)]
+pub(crate) struct SessionLifetimes {
+    pub(crate) access_secs: i64,
+    pub(crate) refresh_secs: i64,
+}
+
+/// Backoff applied to failed logins and registrations
+#[derive(Debug, Clone)]
+pub(crate) struct ThrottlePolicy {
+    /// Delay after the first failure, doubled on each one after it
+    pub(crate) base_delay_secs: u64,
+    pub(crate) max_delay_secs: u64,
+    /// Consecutive failures that lock the key out
+    pub(crate) lockout_after: u32,
+    pub(crate) lockout_secs: u64,
+}
+
+/// Request budgets of authenticated routes, per user
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimits {
+    pub(crate) default: Budget,
+    /// Budgets keyed by route path, e.g. `/invite`
+    pub(crate) routes: HashMap<String, Budget>,
+}
+
+impl RateLimits {
+    pub(crate) fn budget(&self, route: &str) -> &Budget {
+        self.routes.get(route).unwrap_or(&self.default)
+    }
+}
+
+/// `capacity` requests, refilled evenly over `period_secs`. Written as
+/// `capacity/period_secs`, e.g. `10/60`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Budget {
+    pub(crate) capacity: u32,
+    pub(crate) period_secs: u64,
+}
+
+impl Budget {
+    const fn new(capacity: u32, period_secs: u64) -> Self {
+        Self {
+            capacity,
+            period_secs,
+        }
+    }
+}
+
+impl FromStr for Budget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (capacity, period) = s
+            .split_once('/')
+            .ok_or_else(|| format!("expected capacity/seconds, got {s}"))?;
+        let capacity: u32 = capacity.trim().parse().map_err(|_| "invalid capacity")?;
+        let period_secs: u64 = period.trim().parse().map_err(|_| "invalid period")?;
+        if capacity == 0 || period_secs == 0 {
+            return Err("capacity and period must be positive".to_string());
+        }
+        Ok(Self::new(capacity, period_secs))
+    }
+}
+
+/// Set of characters written as a list of characters and ranges, e.g.
+/// `a-zA-Z0-9_-`. A `-` at the start or end is taken literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Charset(Vec<(char, char)>);
+
+impl Charset {
+    pub(crate) fn contains(&self, c: char) -> bool {
+        self.0.iter().any(|&(lo, hi)| lo <= c && c <= hi)
+    }
+}
+
+impl FromStr for Charset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut ranges = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if i + 2 < chars.len() && chars[i + 1] == '-' {
+                let (lo, hi) = (chars[i], chars[i + 2]);
+                if lo > hi {
+                    return Err(format!("invalid range {lo}-{hi}"));
+                }
+                ranges.push((lo, hi));
+                i += 3;
+            } else {
+                ranges.push((chars[i], chars[i]));
+                i += 1;
+            }
+        }
+        if ranges.is_empty() {
+            return Err("empty charset".to_string());
+        }
+        Ok(Self(ranges))
+    }
+}
+
+fn var<T: FromStr>(name: &str, default: T) -> T {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{name} has an invalid value")),
+        Err(_) => default,
+    }
+}
+
+fn list(name: &str, default: &[&str]) -> Vec<String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(_) => default.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
+/// Comma separated `key=value` pairs
+fn pairs<T: FromStr + Clone>(name: &str, default: &[(&str, T)]) -> HashMap<String, T> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|pair| {
+                let (key, value) = pair
+                    .split_once('=')
+                    .unwrap_or_else(|| panic!("{name} has an invalid value"));
+                let value = value
+                    .trim()
+                    .parse()
+                    .unwrap_or_else(|_| panic!("{name} has an invalid value"));
+                (key.trim().to_string(), value)
+            })
+            .collect(),
+        Err(_) => default
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+    }
+}
+
+/// State shared by every handler
+#[derive(Clone)]
+pub(crate) struct AppState {
+    pub(crate) pool: PgPool,
+    pub(crate) config: Arc<Config>,
+    pub(crate) login_attempts: Arc<dyn AttemptStore>,
+    pub(crate) api_buckets: Arc<TokenBuckets>,
+    pub(crate) presence: Arc<Presence>,
+    pub(crate) hub: Arc<Hub>,
+    /// Present when an external engine is configured
+    pub(crate) engines: Option<Arc<uci::Pool>>,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn AttemptStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.login_attempts.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<TokenBuckets> {
+    fn from_ref(state: &AppState) -> Self {
+        state.api_buckets.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Presence> {
+    fn from_ref(state: &AppState) -> Self {
+        state.presence.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Hub> {
+    fn from_ref(state: &AppState) -> Self {
+        state.hub.clone()
+    }
+}
+
+impl FromRef<AppState> for Option<Arc<uci::Pool>> {
+    fn from_ref(state: &AppState) -> Self {
+        state.engines.clone()
+    }
+}
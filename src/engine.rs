@@ -0,0 +1,234 @@
+//! Minimal chess engine backing the built-in computer opponents and analysis
+//! when there is no external engine: alpha-beta search over material and
+//! piece-square tables
+
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece, Square, ALL_SQUARES};
+
+#[cfg(test)]
+mod test;
+
+/// Score of being mated now, mates further away score a bit less
+pub(crate) const MATE: i32 = 100_000;
+const INFINITY: i32 = MATE + 1;
+
+/// Best move for the side to move, looking `depth` plies ahead before
+/// settling captures. `None` when the game is over.
+pub(crate) fn best_move(board: &Board, depth: u8) -> Option<ChessMove> {
+    search(board, depth).map(|(best, _)| best)
+}
+
+/// Like [`best_move`], along with its score for the side to move. Being
+/// mated in `n` plies scores `-(MATE - n)`.
+pub(crate) fn search(board: &Board, depth: u8) -> Option<(ChessMove, i32)> {
+    let mut best = None;
+    let mut alpha = -INFINITY;
+
+    for cmove in ordered(board) {
+        let score = -negamax(
+            &board.make_move_new(cmove),
+            depth.saturating_sub(1),
+            -INFINITY,
+            -alpha,
+            1,
+        );
+        if best.is_none() || score > alpha {
+            alpha = score;
+            best = Some(cmove);
+        }
+    }
+
+    best.map(|best| (best, alpha))
+}
+
+fn negamax(board: &Board, depth: u8, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+    match board.status() {
+        BoardStatus::Checkmate => return -(MATE - ply),
+        BoardStatus::Stalemate => return 0,
+        BoardStatus::Ongoing => {}
+    }
+    if depth == 0 {
+        return quiesce(board, alpha, beta);
+    }
+
+    for cmove in ordered(board) {
+        let score = -negamax(
+            &board.make_move_new(cmove),
+            depth - 1,
+            -beta,
+            -alpha,
+            ply + 1,
+        );
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+
+    alpha
+}
+
+/// Search captures only, so the evaluation is not taken in the middle of
+/// an exchange
+fn quiesce(board: &Board, mut alpha: i32, beta: i32) -> i32 {
+    let standing = evaluate(board);
+    if standing >= beta {
+        return beta;
+    }
+    alpha = alpha.max(standing);
+
+    let mut captures = MoveGen::new_legal(board);
+    captures.set_iterator_mask(*board.color_combined(!board.side_to_move()));
+    let mut captures: Vec<ChessMove> = captures.collect();
+    captures.sort_by_key(|&cmove| -gain(board, cmove));
+
+    for cmove in captures {
+        let score = -quiesce(&board.make_move_new(cmove), -beta, -alpha);
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+
+    alpha
+}
+
+/// Legal moves, most valuable victims taken by the least valuable
+/// attackers first, which lets alpha-beta cut more
+fn ordered(board: &Board) -> Vec<ChessMove> {
+    let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+    moves.sort_by_key(|&cmove| -gain(board, cmove));
+    moves
+}
+
+fn gain(board: &Board, cmove: ChessMove) -> i32 {
+    let victim = board.piece_on(cmove.get_dest()).map_or(0, value);
+    let attacker = board.piece_on(cmove.get_source()).map_or(0, value);
+    let promotion = cmove.get_promotion().map_or(0, value);
+    if victim == 0 && promotion == 0 {
+        0
+    } else {
+        victim * 10 - attacker + promotion
+    }
+}
+
+/// Static evaluation in centipawns, from the side to move's point of view
+pub(crate) fn evaluate(board: &Board) -> i32 {
+    let mut score = 0;
+    for square in ALL_SQUARES {
+        let (Some(piece), Some(color)) = (board.piece_on(square), board.color_on(square)) else {
+            continue;
+        };
+        let worth = value(piece) + placement(piece, color, square);
+        score += if color == Color::White { worth } else { -worth };
+    }
+
+    if board.side_to_move() == Color::White {
+        score
+    } else {
+        -score
+    }
+}
+
+fn value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 100,
+        Piece::Knight => 320,
+        Piece::Bishop => 330,
+        Piece::Rook => 500,
+        Piece::Queen => 900,
+        Piece::King => 20_000,
+    }
+}
+
+/// Bonus of a piece on a square. Tables are written from white's side,
+/// with the eighth rank first.
+fn placement(piece: Piece, color: Color, square: Square) -> i32 {
+    let rank = square.get_rank().to_index();
+    let row = match color {
+        Color::White => 7 - rank,
+        Color::Black => rank,
+    };
+    let index = row * 8 + square.get_file().to_index();
+
+    let table = match piece {
+        Piece::Pawn => &PAWN,
+        Piece::Knight => &KNIGHT,
+        Piece::Bishop => &BISHOP,
+        Piece::Rook => &ROOK,
+        Piece::Queen => &QUEEN,
+        Piece::King => &KING,
+    };
+    table[index]
+}
+
+#[rustfmt::skip]
+const PAWN: [i32; 64] = [
+     0,  0,   0,   0,   0,   0,  0,  0,
+    50, 50,  50,  50,  50,  50, 50, 50,
+    10, 10,  20,  30,  30,  20, 10, 10,
+     5,  5,  10,  25,  25,  10,  5,  5,
+     0,  0,   0,  20,  20,   0,  0,  0,
+     5, -5, -10,   0,   0, -10, -5,  5,
+     5, 10,  10, -20, -20,  10, 10,  5,
+     0,  0,   0,   0,   0,   0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20, -10, -10, -5, -5, -10, -10, -20,
+    -10,   0,   0,  0,  0,   0,   0, -10,
+    -10,   0,   5,  5,  5,   5,   0, -10,
+     -5,   0,   5,  5,  5,   5,   0,  -5,
+      0,   0,   5,  5,  5,   5,   0,  -5,
+    -10,   5,   5,  5,  5,   5,   0, -10,
+    -10,   0,   5,  0,  0,   0,   0, -10,
+    -20, -10, -10, -5, -5, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const KING: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
@@ -0,0 +1,59 @@
+use std::str::FromStr;
+
+use super::*;
+
+fn position(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+#[test]
+fn start_position_is_balanced() {
+    assert_eq!(evaluate(&Board::default()), 0);
+}
+
+#[test]
+fn evaluation_is_from_the_side_to_move() {
+    // White is a queen up
+    let white = position("4k3/8/8/8/8/8/8/3QK3 w - - 0 1");
+    let black = position("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+
+    assert!(evaluate(&white) > 0);
+    assert_eq!(evaluate(&white), -evaluate(&black));
+}
+
+#[test]
+fn finds_mate_in_one() {
+    let board = position("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    let mate = ChessMove::new(Square::A1, Square::A8, None);
+
+    for depth in 1..=3 {
+        assert_eq!(search(&board, depth), Some((mate, MATE - 1)));
+    }
+}
+
+#[test]
+fn takes_a_hanging_queen() {
+    let board = position("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1");
+
+    assert_eq!(
+        best_move(&board, 2),
+        Some(ChessMove::new(Square::D2, Square::D5, None))
+    );
+}
+
+#[test]
+fn does_not_take_a_defended_pawn_with_the_queen() {
+    let board = position("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1");
+
+    assert_ne!(
+        best_move(&board, 2),
+        Some(ChessMove::new(Square::D1, Square::D5, None))
+    );
+}
+
+#[test]
+fn no_move_when_the_game_is_over() {
+    let mated = position("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+
+    assert_eq!(best_move(&mated, 3), None);
+}
@@ -0,0 +1,585 @@
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use chess::{Board, BoardStatus, ChessMove};
+use sqlx::{error::ErrorKind, PgConnection};
+use tracing::error;
+
+use crate::{
+    arena,
+    hub::{GameEvent, UserEvent},
+    notation, opening, position, rating, tournament,
+};
+
+pub(crate) mod premove;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl Outcome {
+    /// Result as written in a PGN `Result` tag
+    pub(crate) fn as_pgn(&self) -> &'static str {
+        match self {
+            Outcome::WhiteWins => "1-0",
+            Outcome::BlackWins => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        }
+    }
+
+    /// Score of the white player
+    fn white_score(&self) -> f64 {
+        match self {
+            Outcome::WhiteWins => 1.0,
+            Outcome::BlackWins => 0.0,
+            Outcome::Draw => 0.5,
+        }
+    }
+}
+
+/// Players of a game, active or finished
+#[derive(sqlx::FromRow, Debug)]
+pub(crate) struct Players {
+    pub(crate) player_w: String,
+    pub(crate) player_b: String,
+    pub(crate) active: bool,
+}
+
+impl Players {
+    pub(crate) fn includes(&self, username: &str) -> bool {
+        self.player_w == username || self.player_b == username
+    }
+}
+
+pub(crate) async fn players(conn: &mut PgConnection, id: i64) -> Result<Players, StatusCode> {
+    sqlx::query_as!(
+        Players,
+        r#"
+        SELECT player_w as "player_w!", player_b as "player_b!", active as "active!"
+        FROM (
+            SELECT player_w, player_b, true as active
+            FROM games.t_active
+            WHERE id = $1
+
+            UNION ALL
+
+            -- Guests of imported games are not the members sharing their name
+            SELECT
+                CASE WHEN guest_w THEN '' ELSE player_w END,
+                CASE WHEN guest_b THEN '' ELSE player_b END,
+                false as active
+            FROM games.t_finished
+            WHERE id = $1
+        ) g
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting players {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// What a game is played for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Event {
+    Casual,
+    Tournament(i64),
+    Arena(i64),
+}
+
+/// Start a game from the initial position, returning its id and the event
+/// announcing it to the players. Two players can only have one casual game with the
+/// same colours at once.
+pub(crate) async fn create(
+    conn: &mut PgConnection,
+    player_w: &str,
+    player_b: &str,
+    public: bool,
+    takebacks: bool,
+    event: Event,
+) -> Result<(i64, UserEvent), StatusCode> {
+    let (tournament_id, arena_id) = match event {
+        Event::Casual => (None, None),
+        Event::Tournament(id) => (Some(id), None),
+        Event::Arena(id) => (None, Some(id)),
+    };
+    let game = sqlx::query!(
+        "
+        INSERT INTO games.t_active(
+            player_w,
+            player_b,
+            fen,
+            start_pos,
+            public,
+            takebacks,
+            tournament_id,
+            arena_id
+        )
+        VALUES ($1, $2, $3, $3, $4, $5, $6, $7)
+        RETURNING id, fen
+        ",
+        player_w,
+        player_b,
+        Board::default().to_string(),
+        public,
+        takebacks,
+        tournament_id,
+        arena_id,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        if err
+            .as_database_error()
+            .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
+        {
+            StatusCode::NOT_ACCEPTABLE
+        } else {
+            error!("Error inserting new game {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    let start = UserEvent::GameStart {
+        id: game.id,
+        player_w: player_w.to_string(),
+        player_b: player_b.to_string(),
+        fen: game.fen,
+    };
+    Ok((game.id, start))
+}
+
+/// A move stored by [`play_move`]
+#[derive(Debug, Clone)]
+pub(crate) struct Played {
+    pub(crate) id: i64,
+    /// Who made the move
+    pub(crate) player: String,
+    /// Who is to move after it
+    pub(crate) opponent: String,
+    pub(crate) san: String,
+    pub(crate) chess_move: ChessMove,
+    /// Board the move was played on
+    pub(crate) previous: Board,
+    pub(crate) board: Board,
+    /// Set when the move ended the game
+    pub(crate) outcome: Option<Outcome>,
+}
+
+impl Played {
+    /// Events telling subscribers about this move
+    pub(crate) fn events(&self) -> Vec<GameEvent> {
+        let mut events = vec![GameEvent::Move {
+            san: self.san.clone(),
+            fen: self.board.to_string(),
+        }];
+        if let Some(outcome) = self.outcome {
+            events.push(GameEvent::Finished {
+                result: outcome.as_pgn(),
+            });
+        }
+        events
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CGame {
+    id: i64,
+    fen: String,
+    last_move: Option<i32>,
+    player_w: String,
+    player_b: String,
+}
+
+/// Play `san` for `username` in game `id`, finishing the game if the move
+/// ends it
+pub(crate) async fn play_move(
+    trx: &mut PgConnection,
+    id: i64,
+    username: &str,
+    san: &str,
+) -> Result<Played, StatusCode> {
+    // Get details for the current game
+    let cgame = sqlx::query_as!(
+        CGame,
+        "
+        SELECT 
+            id, 
+            fen, 
+            COALESCE(mo.move_num, 0::int) as last_move,
+            player_w,
+            player_b
+        FROM games.t_active ac
+            LEFT JOIN games.t_moves mo ON mo.id_game = ac.id
+        WHERE ac.id = $1
+        ORDER BY mo.move_num DESC
+        LIMIT 1
+        FOR UPDATE OF ac
+        ",
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    // Interpret game from string
+    let board = Board::from_str(&cgame.fen).map_err(|err| {
+        error!("Error interpreting fen from database {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Check if it's my turn to move
+    let player_to_move = match board.side_to_move() {
+        chess::Color::White => &cgame.player_w,
+        chess::Color::Black => &cgame.player_b,
+    };
+
+    if player_to_move != username {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    arena::tick(trx, cgame.id, board.side_to_move() == chess::Color::White).await?;
+
+    // Interpret move in this game
+    let cmove: ChessMove =
+        notation::parse_san(&board, san).map_err(|_| StatusCode::NOT_ACCEPTABLE)?;
+
+    // Store the move as standard notation, whatever the user typed
+    let san = notation::san(&board, cmove);
+
+    // Make the move in this board
+    let previous_board = board;
+    let board = board.make_move_new(cmove);
+
+    // Check if the position has repeated 3 times
+    let repeated = sqlx::query!(
+        "
+        SELECT 
+        FROM games.t_moves
+        WHERE id_game = $1
+          AND previous_fen = $2
+        GROUP BY previous_fen
+        HAVING COUNT(1) >= 2
+        ",
+        cgame.id,
+        board.to_string(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error counting repetitions {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .is_some();
+
+    // Insert move in the database
+    sqlx::query!(
+        "
+        INSERT INTO games.t_moves(id_game, san, previous_fen, move_num)
+        VALUES($1, $2, $3, $4)
+        ",
+        cgame.id,
+        san,
+        cgame.fen,
+        cgame.last_move.unwrap_or(0) + 1,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error inserting move {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    sqlx::query!(
+        "
+        UPDATE games.t_active
+        SET fen = $1
+        WHERE id = $2
+        ",
+        board.to_string(),
+        cgame.id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error inserting new board {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // A pending takeback no longer refers to the last move
+    sqlx::query!(
+        "
+        DELETE FROM games.t_takebacks
+        WHERE id_game = $1
+        ",
+        cgame.id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error clearing takeback request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // End the game if needed
+    let outcome = match board.status() {
+        // The side to move has been mated
+        BoardStatus::Checkmate => match board.side_to_move() {
+            chess::Color::White => Some(Outcome::BlackWins),
+            chess::Color::Black => Some(Outcome::WhiteWins),
+        },
+        BoardStatus::Stalemate => Some(Outcome::Draw),
+        BoardStatus::Ongoing if repeated => Some(Outcome::Draw),
+        BoardStatus::Ongoing => None,
+    };
+
+    if let Some(outcome) = outcome {
+        finish(trx, cgame.id, outcome).await?;
+    }
+
+    let opponent = match board.side_to_move() {
+        chess::Color::White => cgame.player_w,
+        chess::Color::Black => cgame.player_b,
+    };
+
+    Ok(Played {
+        id: cgame.id,
+        player: username.to_string(),
+        opponent,
+        san,
+        chess_move: cmove,
+        previous: previous_board,
+        board,
+        outcome,
+    })
+}
+
+#[derive(sqlx::FromRow)]
+struct AGame {
+    player_w: String,
+    player_b: String,
+    start_pos: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct CMove {
+    san: String,
+}
+
+/// Move an active game to `games.t_finished` and record its result.
+///
+/// Every move must already be stored in `games.t_moves`, since they are
+/// cascaded away together with the active game.
+pub(crate) async fn finish(
+    trx: &mut PgConnection,
+    id: i64,
+    outcome: Outcome,
+) -> Result<(), StatusCode> {
+    let game = sqlx::query_as!(
+        AGame,
+        "
+        SELECT player_w, player_b, start_pos
+        FROM games.t_active
+        WHERE id = $1
+        ",
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting game to finish {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let moves = sqlx::query_as!(
+        CMove,
+        "
+        SELECT san
+        FROM games.t_moves
+        WHERE id_game = $1
+        ORDER BY move_num
+        ",
+        id,
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting moves {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .into_iter()
+    .map(|x| x.san)
+    .collect::<Vec<String>>()
+    .join(" ");
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_active
+        WHERE id = $1
+        ",
+        id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting active board {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let opening = opening::classify(&game.start_pos, &moves);
+    sqlx::query!(
+        "
+        INSERT INTO games.t_finished(
+            id,
+            start_pos,
+            moves,
+            player_w,
+            player_b,
+            result,
+            eco,
+            opening
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ",
+        id,
+        game.start_pos,
+        moves,
+        game.player_w,
+        game.player_b,
+        outcome.as_pgn(),
+        opening.map(|opening| opening.eco),
+        opening.map(|opening| opening.name),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error inserting finished game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    position::index(trx, id, &game.start_pos, &moves).await?;
+    tournament::record(trx, id, outcome).await?;
+    arena::record(trx, id, outcome).await?;
+
+    update_ratings(trx, &game.player_w, &game.player_b, outcome).await?;
+
+    let score = outcome.white_score();
+    record_result(trx, &game.player_w, score).await?;
+    record_result(trx, &game.player_b, 1.0 - score).await?;
+
+    Ok(())
+}
+
+async fn update_ratings(
+    trx: &mut PgConnection,
+    player_w: &str,
+    player_b: &str,
+    outcome: Outcome,
+) -> Result<(), StatusCode> {
+    if player_w == player_b {
+        return Ok(());
+    }
+
+    let ratings = sqlx::query!(
+        "
+        SELECT username, rating
+        FROM users.basic_info
+        WHERE username = $1
+           OR username = $2
+        FOR UPDATE
+        ",
+        player_w,
+        player_b,
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting ratings {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let rating_of = |username: &str| {
+        ratings
+            .iter()
+            .find(|r| r.username == username)
+            .map(|r| r.rating)
+            .ok_or_else(|| {
+                error!("Player {username} of a finished game does not exist");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+    };
+
+    let (new_w, new_b) = rating::elo(
+        rating_of(player_w)?,
+        rating_of(player_b)?,
+        outcome.white_score(),
+    );
+
+    for (username, rating) in [(player_w, new_w), (player_b, new_b)] {
+        sqlx::query!(
+            "
+            UPDATE users.basic_info
+            SET rating = $1
+            WHERE username = $2
+            ",
+            rating,
+            username,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error updating rating {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Add a finished game to the per day summary the leaderboard is built from
+async fn record_result(
+    trx: &mut PgConnection,
+    username: &str,
+    score: f64,
+) -> Result<(), StatusCode> {
+    let (wins, draws, losses) = if score == 1.0 {
+        (1, 0, 0)
+    } else if score == 0.0 {
+        (0, 0, 1)
+    } else {
+        (0, 1, 0)
+    };
+
+    sqlx::query!(
+        "
+        INSERT INTO games.t_daily_results(username, wins, draws, losses)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (username, day) DO UPDATE
+        SET wins = t_daily_results.wins + EXCLUDED.wins,
+            draws = t_daily_results.draws + EXCLUDED.draws,
+            losses = t_daily_results.losses + EXCLUDED.losses
+        ",
+        username,
+        wins,
+        draws,
+        losses,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error recording result {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
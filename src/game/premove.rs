@@ -0,0 +1,128 @@
+//! Conditional moves queued by a player while waiting for the opponent
+
+use axum::http::StatusCode;
+use chess::{Board, BoardStatus, ChessMove};
+use serde::Serialize;
+use sqlx::PgConnection;
+use tracing::error;
+
+use super::{play_move, Played};
+use crate::notation;
+
+#[cfg(test)]
+mod test;
+
+/// First move of a line that can not be played
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct InvalidLine {
+    line: usize,
+    ply: usize,
+}
+
+/// Check that every line alternates legal moves from `board`, starting with
+/// the side to move, and ends with a reply of ours
+pub(crate) fn validate(board: &Board, lines: &[Vec<String>]) -> Result<(), InvalidLine> {
+    for (line, moves) in lines.iter().enumerate() {
+        if moves.is_empty() || moves.len() % 2 != 0 {
+            return Err(InvalidLine {
+                line,
+                ply: moves.len(),
+            });
+        }
+
+        let mut board = *board;
+        for (ply, san) in moves.iter().enumerate() {
+            let invalid = InvalidLine { line, ply };
+            if board.status() != BoardStatus::Ongoing {
+                return Err(invalid);
+            }
+            let cmove = notation::parse_san(&board, san).map_err(|_| invalid)?;
+            board = board.make_move_new(cmove);
+        }
+    }
+
+    Ok(())
+}
+
+/// After `played` is stored, play the opponent's queued reply to it, if any.
+///
+/// Lines that do not start with the played move are discarded, the rest
+/// are kept without the move and its reply.
+pub(crate) async fn respond(
+    trx: &mut PgConnection,
+    played: &Played,
+) -> Result<Option<Played>, StatusCode> {
+    // Finished games take their queued moves with them
+    if played.outcome.is_some() {
+        return Ok(None);
+    }
+
+    let mut lines = sqlx::query!(
+        "
+        DELETE FROM games.t_premoves
+        WHERE id_game = $1
+          AND player <> $2
+        RETURNING id, player, moves
+        ",
+        played.id,
+        played.player,
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting premoves {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    lines.sort_by_key(|line| line.id);
+
+    let mut reply: Option<(String, ChessMove)> = None;
+    let mut remaining = Vec::new();
+    let mut premover = None;
+
+    for line in lines {
+        let answers = notation::parse_san(&played.previous, &line.moves[0])
+            .is_ok_and(|cmove| cmove == played.chess_move);
+        let Some(cmove) = notation::parse_san(&played.board, &line.moves[1])
+            .ok()
+            .filter(|_| answers)
+        else {
+            continue;
+        };
+
+        // On conflicting replies the line queued first wins
+        match &reply {
+            Some((_, chosen)) if *chosen != cmove => continue,
+            Some(_) => {}
+            None => reply = Some((line.moves[1].clone(), cmove)),
+        }
+
+        if line.moves.len() > 2 {
+            remaining.push(line.moves[2..].to_vec());
+        }
+        premover = Some(line.player);
+    }
+
+    let (Some((san, _)), Some(premover)) = (reply, premover) else {
+        return Ok(None);
+    };
+
+    for moves in remaining {
+        sqlx::query!(
+            "
+            INSERT INTO games.t_premoves(id_game, player, moves)
+            VALUES ($1, $2, $3)
+            ",
+            played.id,
+            premover,
+            &moves,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error storing premoves {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    play_move(trx, played.id, &premover, &san).await.map(Some)
+}
@@ -0,0 +1,57 @@
+use std::str::FromStr;
+
+use super::*;
+
+fn line(moves: &str) -> Vec<String> {
+    moves.split_whitespace().map(str::to_string).collect()
+}
+
+#[test]
+fn legal_lines() {
+    // Black to move after 1. e4
+    let board =
+        Board::from_str("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+
+    assert_eq!(
+        validate(&board, &[line("e5 Nf3 Nc6 Bb5"), line("c5 Nf3")]),
+        Ok(())
+    );
+}
+
+#[test]
+fn illegal_moves_are_located() {
+    let board = Board::default();
+
+    assert_eq!(
+        validate(&board, &[line("e4 e5"), line("d4 d5 Qxd5 Qxd5")]),
+        Err(InvalidLine { line: 1, ply: 2 })
+    );
+    assert_eq!(
+        validate(&board, &[line("e5 e4")]),
+        Err(InvalidLine { line: 0, ply: 0 })
+    );
+}
+
+#[test]
+fn lines_end_with_a_reply() {
+    let board = Board::default();
+
+    assert_eq!(
+        validate(&board, &[line("e4 e5 Nf3")]),
+        Err(InvalidLine { line: 0, ply: 3 })
+    );
+    assert_eq!(
+        validate(&board, &[vec![]]),
+        Err(InvalidLine { line: 0, ply: 0 })
+    );
+}
+
+#[test]
+fn no_moves_after_mate() {
+    let board = Board::default();
+
+    assert_eq!(
+        validate(&board, &[line("f3 e5 g4 Qh4# a3 a6")]),
+        Err(InvalidLine { line: 0, ply: 4 })
+    );
+}
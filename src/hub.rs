@@ -0,0 +1,141 @@
+use std::{collections::HashMap, hash::Hash, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+#[cfg(test)]
+mod test;
+
+/// Events delivered to the subscribers of a game
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum GameEvent {
+    Move {
+        san: String,
+        fen: String,
+    },
+    Finished {
+        result: &'static str,
+    },
+    TakebackRequested {
+        by: String,
+    },
+    TakebackDeclined,
+    /// A player of an arena game gave up half their time
+    Berserk {
+        by: String,
+    },
+    /// The last move was undone, leaving the board at `fen`
+    TakenBack {
+        fen: String,
+    },
+    Chat {
+        author: String,
+        body: String,
+        sent_at: DateTime<Utc>,
+    },
+}
+
+/// Events delivered to a user, whatever game they are about
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum UserEvent {
+    /// Somebody invited the user
+    Challenge {
+        inviter: String,
+        public: bool,
+        takebacks: bool,
+    },
+    /// A game of the user began
+    GameStart {
+        id: i64,
+        player_w: String,
+        player_b: String,
+        fen: String,
+    },
+}
+
+/// Events buffered per channel for slow subscribers
+const CAPACITY: usize = 64;
+
+/// In-process broadcast of game and user events
+#[derive(Default)]
+pub(crate) struct Hub {
+    games: Channels<i64, GameEvent>,
+    users: Channels<String, UserEvent>,
+}
+
+impl Hub {
+    pub(crate) fn subscribe(&self, game: i64) -> broadcast::Receiver<GameEvent> {
+        self.games.subscribe(game)
+    }
+
+    /// Send an event to whoever is subscribed to the game. Must be called
+    /// once the change is committed. Subscriptions end with the game.
+    pub(crate) fn publish(&self, game: i64, event: GameEvent) {
+        let finished = matches!(event, GameEvent::Finished { .. });
+        self.games.publish(&game, event);
+        if finished {
+            self.games.close(&game);
+        }
+    }
+
+    /// Drop the channel of a game found to be over after subscribing to it
+    pub(crate) fn close(&self, game: i64) {
+        self.games.close(&game);
+    }
+
+    pub(crate) fn subscribe_user(&self, username: &str) -> broadcast::Receiver<UserEvent> {
+        self.users.subscribe(username.to_string())
+    }
+
+    /// Like [`Hub::publish`], for events about a user
+    pub(crate) fn publish_user(&self, username: &str, event: UserEvent) {
+        self.users.publish(&username.to_string(), event);
+    }
+}
+
+struct Channels<K, E> {
+    senders: Mutex<HashMap<K, broadcast::Sender<E>>>,
+}
+
+impl<K, E> Default for Channels<K, E> {
+    fn default() -> Self {
+        Self {
+            senders: Mutex::default(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, E: Clone> Channels<K, E> {
+    fn subscribe(&self, key: K) -> broadcast::Receiver<E> {
+        let mut senders = self.senders.lock().expect("hub mutex poisoned");
+        // Forget the channels whose subscribers all went away
+        senders.retain(|_, sender| sender.receiver_count() > 0);
+        senders
+            .entry(key)
+            .or_insert_with(|| broadcast::channel(CAPACITY).0)
+            .subscribe()
+    }
+
+    fn publish(&self, key: &K, event: E) {
+        let mut senders = self.senders.lock().expect("hub mutex poisoned");
+        if let Some(sender) = senders.get(key) {
+            if sender.send(event).is_err() {
+                // Nobody is listening anymore
+                senders.remove(key);
+            }
+        }
+    }
+
+    /// Drop the channel, its subscribers still get the events already sent
+    fn close(&self, key: &K) {
+        self.senders.lock().expect("hub mutex poisoned").remove(key);
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.senders.lock().expect("hub mutex poisoned").len()
+    }
+}
@@ -0,0 +1,56 @@
+use super::*;
+
+fn chat() -> GameEvent {
+    GameEvent::Chat {
+        author: "alice".to_string(),
+        body: "hi".to_string(),
+        sent_at: Utc::now(),
+    }
+}
+
+#[test]
+fn forgets_channels_without_subscribers() {
+    let hub = Hub::default();
+    let first = hub.subscribe(1);
+    let _second = hub.subscribe(2);
+    let user = hub.subscribe_user("alice");
+    drop(first);
+    drop(user);
+
+    let _third = hub.subscribe(3);
+    assert_eq!(hub.games.len(), 2);
+
+    let _bob = hub.subscribe_user("bob");
+    assert_eq!(hub.users.len(), 1);
+}
+
+#[test]
+fn closes_finished_games() {
+    let hub = Hub::default();
+    let mut events = hub.subscribe(1);
+
+    hub.publish(1, chat());
+    hub.publish(1, GameEvent::Finished { result: "1-0" });
+    assert_eq!(hub.games.len(), 0);
+
+    assert!(matches!(events.try_recv(), Ok(GameEvent::Chat { .. })));
+    assert!(matches!(events.try_recv(), Ok(GameEvent::Finished { .. })));
+    assert!(matches!(
+        events.try_recv(),
+        Err(broadcast::error::TryRecvError::Closed)
+    ));
+}
+
+#[test]
+fn late_subscribers_of_finished_games_are_closed() {
+    let hub = Hub::default();
+    hub.publish(1, GameEvent::Finished { result: "1-0" });
+
+    let mut events = hub.subscribe(1);
+    hub.close(1);
+    assert_eq!(hub.games.len(), 0);
+    assert!(matches!(
+        events.try_recv(),
+        Err(broadcast::error::TryRecvError::Closed)
+    ));
+}
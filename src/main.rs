@@ -1,16 +1,37 @@
 use axum::{
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
+use config::{AppState, Config};
 use core::time::Duration;
 use sqlx::postgres::PgPoolOptions;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 #[cfg(debug_assertions)]
 use tracing::Level;
 
+mod analysis;
+mod arena;
 pub(crate) mod authentication;
+mod bot;
+mod config;
+mod engine;
+mod game;
+mod hub;
+mod notation;
+mod opening;
+mod pgn;
+mod position;
+mod presence;
+mod puzzle;
+mod rating;
 mod route;
+mod social;
+mod team;
+mod throttle;
+mod tournament;
+mod uci;
+mod validation;
 
 #[tokio::main]
 #[tracing::instrument]
@@ -37,6 +58,72 @@ async fn main() {
         .await
         .expect("can't connect to database");
 
+    let config = Config::from_env();
+    let state = AppState {
+        pool,
+        login_attempts: Arc::new(throttle::MemoryStore::new(config.login_throttle.clone())),
+        api_buckets: Arc::new(throttle::TokenBuckets::new(config.rate_limits.clone())),
+        presence: Arc::new(presence::Presence::new(Duration::from_secs(
+            config.presence_timeout_secs,
+        ))),
+        hub: Arc::default(),
+        engines: config.uci.as_ref().map(|uci| {
+            Arc::new(uci::Pool::new(
+                uci.path.clone(),
+                uci.pool_size,
+                Duration::from_secs(uci.timeout_secs),
+            ))
+        }),
+        config: Arc::new(config),
+    };
+    bot::register(&state.pool, &state.config.bots, false)
+        .await
+        .expect("can't register bots");
+    if let Some(uci) = &state.config.uci {
+        bot::register(&state.pool, &uci.bots, true)
+            .await
+            .expect("can't register bots");
+    }
+
+    tokio::spawn(position::backfill(state.pool.clone()));
+    tokio::spawn(opening::backfill(state.pool.clone()));
+    tokio::spawn(tournament::direct(state.pool.clone(), state.hub.clone()));
+    tokio::spawn(arena::direct(state.pool.clone(), state.hub.clone()));
+    tokio::spawn(bot::resume(
+        state.pool.clone(),
+        state.hub.clone(),
+        state.engines.clone(),
+    ));
+    if let Some(path) = &state.config.puzzles_csv {
+        tokio::spawn(puzzle::import(state.pool.clone(), path.clone()));
+    }
+
+    let depth = state
+        .config
+        .uci
+        .as_ref()
+        .map_or(analysis::BUILTIN_DEPTH, |uci| uci.analysis_depth);
+    for _ in 0..state.config.analysis_workers {
+        tokio::spawn(analysis::work(
+            state.pool.clone(),
+            state.engines.clone(),
+            depth,
+        ));
+    }
+
+    let login_throttle = middleware::from_fn_with_state(state.clone(), throttle::login);
+
+    // Spectating needs a login unless configured otherwise
+    let spectate = Router::new()
+        .route("/games/live", get(route::spectate::live))
+        .route("/games/watch", get(route::spectate::watch))
+        .route("/games/watch/events", get(route::spectate::events));
+    let (logged_spectate, anonymous_spectate) = if state.config.anonymous_spectators {
+        (Router::new(), spectate)
+    } else {
+        (spectate, Router::new())
+    };
+
     // build our application with a route
     let app = Router::new()
         .route("/invite", post(route::game::invite))
@@ -46,14 +133,99 @@ async fn main() {
         .route("/get_board", get(route::game::get_board))
         .route("/make_move", post(route::game::make_move))
         .route("/finished", get(route::game::finished))
+        .route("/import_pgn", post(route::game::import_pgn))
+        .route("/imports", get(route::game::imports))
+        .route("/imports/accept", post(route::game::accept_import))
+        .route("/imports/decline", post(route::game::decline_import))
+        .route("/game/:id/analyze", post(route::game::analyze))
+        .route("/game/:id/analysis", get(route::game::analysis))
+        .route(
+            "/premoves",
+            get(route::game::premoves).post(route::game::set_premoves),
+        )
+        .route("/takeback", post(route::game::takeback))
+        .route("/takeback/accept", post(route::game::accept_takeback))
+        .route("/takeback/decline", post(route::game::decline_takeback))
+        .route("/send_message", post(route::game::send_message))
+        .route("/messages", get(route::game::messages))
+        .route("/subscribe", get(route::game::subscribe))
+        .route("/evaluate", get(route::evaluate::handler))
+        .route("/puzzle/next", get(route::puzzle::next))
+        .route("/puzzle/move", post(route::puzzle::play))
+        .route("/search/position", get(route::position::search))
+        .route("/explorer", get(route::position::explorer))
+        .route("/user", delete(route::user::delete::handler))
+        .route("/user/password", post(route::user::password::handler))
+        .route("/user/settings", post(route::user::settings::handler))
+        .route("/user/bot", post(route::user::bot::handler))
+        .route(
+            "/user/tokens",
+            get(route::user::tokens::handler)
+                .post(route::user::create_token::handler)
+                .delete(route::user::revoke_token::handler),
+        )
+        .route(
+            "/user/:username/games/export",
+            get(route::user::export::handler),
+        )
+        .route("/user/:username/stats", get(route::user::stats::handler))
+        .route("/bot/stream", get(route::bot::handler))
+        .route(
+            "/friends",
+            get(route::friends::list).delete(route::friends::remove),
+        )
+        .route("/friends/request", post(route::friends::request))
+        .route("/friends/accept", post(route::friends::accept))
+        .route(
+            "/user/block",
+            post(route::user::block::handler).delete(route::user::unblock::handler),
+        )
+        .route(
+            "/tournaments",
+            get(route::tournament::list).post(route::tournament::create),
+        )
+        .route("/tournament/:id", get(route::tournament::get))
+        .route("/tournament/:id/join", post(route::tournament::join))
+        .route("/tournament/:id/leave", post(route::tournament::leave))
+        .route("/tournament/:id/start", post(route::tournament::start))
+        .route(
+            "/arenas",
+            get(route::arena::list).post(route::arena::create),
+        )
+        .route("/arena/:id", get(route::arena::get))
+        .route("/arena/:id/join", post(route::arena::join))
+        .route("/arena/:id/leave", post(route::arena::leave))
+        .route("/arena/berserk", post(route::arena::berserk))
+        .route("/teams", post(route::team::create))
+        .route("/team/:id", get(route::team::get))
+        .route("/team/:id/requests", get(route::team::requests))
+        .route("/team/:id/invite", post(route::team::invite))
+        .route("/team/:id/join", post(route::team::join))
+        .route("/team/:id/leave", post(route::team::leave))
+        .route("/team/:id/challenge", post(route::team::challenge))
+        .route("/match/:id", get(route::team::get_match))
+        .route("/match/:id/accept", post(route::team::accept_challenge))
+        .route("/match/:id/decline", post(route::team::decline_challenge))
+        .merge(logged_spectate)
+        // Layers run bottom to top, so users are known when rate limiting
+        .route_layer(middleware::from_fn_with_state(state.clone(), throttle::api))
         .route_layer(middleware::from_fn_with_state(
-            pool.clone(),
+            state.clone(),
             authentication::auth,
         ))
         // `POST /users` goes to `create_user`
-        .route("/user/register", post(route::user::post::handler))
-        .route("/user/login", post(route::user::get::handler))
-        .with_state(pool);
+        .route(
+            "/user/register",
+            post(route::user::post::handler).route_layer(login_throttle.clone()),
+        )
+        .route(
+            "/user/login",
+            post(route::user::get::handler).route_layer(login_throttle),
+        )
+        .route("/user/refresh", post(route::user::refresh::handler))
+        .route("/leaderboard", get(route::leaderboard::handler))
+        .merge(anonymous_spectate)
+        .with_state(state);
 
     // run our app with hyper
     // `axum::Server` is a re-export of `hyper::Server`
@@ -66,7 +238,7 @@ async fn main() {
     ));
     tracing::debug!("listening on {}", addr);
     axum::Server::bind(&addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .await
         .unwrap();
 }
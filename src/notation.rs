@@ -0,0 +1,116 @@
+//! Standard algebraic notation
+
+use chess::{BitBoard, Board, BoardStatus, ChessMove, Error, MoveGen, Piece, EMPTY};
+
+#[cfg(test)]
+mod test;
+
+/// Write `cmove`, legal on `board`, in SAN, e.g. `Nbd2`, `exd6` or `e8=Q+`
+pub(crate) fn san(board: &Board, cmove: ChessMove) -> String {
+    let (source, dest) = (cmove.get_source(), cmove.get_dest());
+    let piece = board.piece_on(source).expect("a move starts on a piece");
+    let captures = board.piece_on(dest).is_some();
+    let mut san = String::new();
+
+    let files = (source.get_file().to_index() as i8 - dest.get_file().to_index() as i8).abs();
+    if piece == Piece::King && files == 2 {
+        san.push_str(if dest.get_file() > source.get_file() {
+            "O-O"
+        } else {
+            "O-O-O"
+        });
+    } else {
+        if piece == Piece::Pawn {
+            // Also covers en passant, where the target square is empty
+            if source.get_file() != dest.get_file() {
+                san.push(file(source));
+                san.push('x');
+            }
+        } else {
+            san.push(letter(piece));
+
+            // Other pieces of the same kind that could go to the same square
+            let rivals = MoveGen::new_legal(board)
+                .filter(|m| {
+                    m.get_dest() == dest
+                        && m.get_source() != source
+                        && board.piece_on(m.get_source()) == Some(piece)
+                })
+                .fold(EMPTY, |rivals, m| {
+                    rivals | BitBoard::from_square(m.get_source())
+                });
+            if rivals != EMPTY {
+                let same_file = rivals
+                    .into_iter()
+                    .any(|s| s.get_file() == source.get_file());
+                let same_rank = rivals
+                    .into_iter()
+                    .any(|s| s.get_rank() == source.get_rank());
+                if !same_file {
+                    san.push(file(source));
+                } else if !same_rank {
+                    san.push(rank(source));
+                } else {
+                    san.push(file(source));
+                    san.push(rank(source));
+                }
+            }
+
+            if captures {
+                san.push('x');
+            }
+        }
+
+        san.push_str(&dest.to_string());
+
+        if let Some(promotion) = cmove.get_promotion() {
+            san.push('=');
+            san.push(letter(promotion));
+        }
+    }
+
+    let after = board.make_move_new(cmove);
+    if after.status() == BoardStatus::Checkmate {
+        san.push('#');
+    } else if *after.checkers() != EMPTY {
+        san.push('+');
+    }
+
+    san
+}
+
+/// Read a move in SAN. Besides what [`ChessMove::from_san`] takes, this
+/// accepts `=` before a promotion, check marks after castling, castling
+/// written with zeros and trailing `!`/`?` annotations.
+pub(crate) fn parse_san(board: &Board, text: &str) -> Result<ChessMove, Error> {
+    let text = text
+        .trim()
+        .trim_end_matches(['!', '?'])
+        .trim_end_matches(['+', '#']);
+    let text = match text {
+        "0-0" => "O-O".to_string(),
+        "0-0-0" => "O-O-O".to_string(),
+        text => text.replace('=', ""),
+    };
+
+    ChessMove::from_san(board, &text)
+}
+
+fn letter(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => 'P',
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Rook => 'R',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+    }
+}
+
+fn file(square: chess::Square) -> char {
+    (b'a' + square.get_file().to_index() as u8) as char
+}
+
+fn rank(square: chess::Square) -> char {
+    (b'1' + square.get_rank().to_index() as u8) as char
+}
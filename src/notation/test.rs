@@ -0,0 +1,78 @@
+use std::str::FromStr;
+
+use chess::Square;
+
+use super::*;
+
+fn position(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+fn san_of(fen: &str, source: Square, dest: Square, promotion: Option<Piece>) -> String {
+    san(&position(fen), ChessMove::new(source, dest, promotion))
+}
+
+#[test]
+fn round_trips_every_legal_move() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    ] {
+        let board = position(fen);
+        for cmove in MoveGen::new_legal(&board) {
+            let written = san(&board, cmove);
+            assert_eq!(
+                parse_san(&board, &written).ok(),
+                Some(cmove),
+                "{written} in {fen}"
+            );
+        }
+    }
+}
+
+#[test]
+fn disambiguates_by_file_then_rank() {
+    let fen = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(san_of(fen, Square::B1, Square::D2, None), "Nbd2");
+
+    let fen = "4k3/8/8/8/R7/8/8/R3K3 w - - 0 1";
+    assert_eq!(san_of(fen, Square::A1, Square::A2, None), "R1a2");
+}
+
+#[test]
+fn marks_captures_promotions_and_checks() {
+    // En passant
+    let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    assert_eq!(san_of(fen, Square::E5, Square::D6, None), "exd6");
+
+    let fen = "7k/4P3/8/8/8/8/8/4K3 w - - 0 1";
+    assert_eq!(
+        san_of(fen, Square::E7, Square::E8, Some(Piece::Queen)),
+        "e8=Q+"
+    );
+
+    let fen = "rnbqkbnr/pppp1ppp/8/4p3/6P1/5P2/PPPPP2P/RNBQKBNR b KQkq - 0 2";
+    assert_eq!(san_of(fen, Square::D8, Square::H4, None), "Qh4#");
+}
+
+#[test]
+fn castles() {
+    let fen = "3k4/8/8/8/8/8/8/R3K2R w KQ - 0 1";
+    assert_eq!(san_of(fen, Square::E1, Square::G1, None), "O-O");
+    assert_eq!(san_of(fen, Square::E1, Square::C1, None), "O-O-O+");
+}
+
+#[test]
+fn parses_common_variants() {
+    let board = position("3k4/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+    let long = ChessMove::new(Square::E1, Square::C1, None);
+    assert_eq!(parse_san(&board, "O-O-O+").ok(), Some(long));
+    assert_eq!(parse_san(&board, "0-0-0").ok(), Some(long));
+
+    let board = position("7k/4P3/8/8/8/8/8/4K3 w - - 0 1");
+    let promotion = ChessMove::new(Square::E7, Square::E8, Some(Piece::Queen));
+    assert_eq!(parse_san(&board, "e8=Q+!").ok(), Some(promotion));
+    assert_eq!(parse_san(&board, "e8Q").ok(), Some(promotion));
+}
@@ -0,0 +1,129 @@
+//! Naming the opening of a game from a bundled ECO table
+
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
+use chess::Board;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::{pgn, position};
+
+#[cfg(test)]
+mod test;
+
+/// Tab separated code, name and moves of each opening
+const ECO: &str = include_str!("opening/eco.tsv");
+
+/// Games classified per query when catching up at startup
+const BACKFILL_BATCH: i64 = 100;
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Opening {
+    pub(crate) eco: &'static str,
+    pub(crate) name: &'static str,
+}
+
+/// Openings by the key of the position their moves lead to. When several
+/// lines reach the same position the first one listed names it.
+fn table() -> &'static HashMap<String, Opening> {
+    static TABLE: OnceLock<HashMap<String, Opening>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for line in ECO.lines().skip(1) {
+            let mut fields = line.split('\t');
+            let (Some(eco), Some(name), Some(moves)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                panic!("malformed ECO line {line}");
+            };
+
+            let moves = &pgn::parse(moves)[0].moves;
+            let moves = pgn::replay(Board::default(), moves)
+                .unwrap_or_else(|_| panic!("illegal moves in ECO line {line}"));
+            let (key, _) = position::positions(Board::default(), &moves.join(" "))
+                .pop()
+                .expect("positions include the start");
+
+            table.entry(key).or_insert(Opening { eco, name });
+        }
+        table
+    })
+}
+
+/// Opening of the deepest position of the game found in the table, so
+/// transpositions are recognised
+pub(crate) fn classify(start_pos: &str, moves: &str) -> Option<&'static Opening> {
+    let start = Board::from_str(start_pos).ok()?;
+    let table = table();
+    position::positions(start, moves)
+        .iter()
+        .rev()
+        .find_map(|(key, _)| table.get(key))
+}
+
+/// Name the openings of games finished before they were classified. Games
+/// outside the table are looked at again on every start.
+pub(crate) async fn backfill(pool: PgPool) {
+    let mut last = 0;
+    let mut named = 0;
+    loop {
+        let games = sqlx::query!(
+            "
+            SELECT id, start_pos, moves
+            FROM games.t_finished
+            WHERE id > $1
+              AND eco IS NULL
+            ORDER BY id
+            LIMIT $2
+            ",
+            last,
+            BACKFILL_BATCH,
+        )
+        .fetch_all(&pool)
+        .await;
+        let games = match games {
+            Ok(games) if games.is_empty() => break,
+            Ok(games) => games,
+            Err(err) => {
+                error!("Error getting games to classify {err}");
+                return;
+            }
+        };
+
+        let (mut ids, mut ecos, mut names) = (Vec::new(), Vec::new(), Vec::new());
+        for game in &games {
+            if let Some(opening) = classify(&game.start_pos, &game.moves) {
+                ids.push(game.id);
+                ecos.push(opening.eco);
+                names.push(opening.name);
+            }
+        }
+
+        let updated = sqlx::query!(
+            "
+            UPDATE games.t_finished fi
+            SET eco = op.eco,
+                opening = op.name
+            FROM UNNEST($1::bigint[], $2::text[], $3::text[]) op(id, eco, name)
+            WHERE fi.id = op.id
+            ",
+            &ids,
+            &ecos as &[&str],
+            &names as &[&str],
+        )
+        .execute(&pool)
+        .await;
+        if let Err(err) = updated {
+            error!("Error storing openings {err}");
+            return;
+        }
+
+        named += ids.len();
+        last = games.last().map_or(last, |game| game.id);
+    }
+
+    if named > 0 {
+        info!("Named the openings of {named} games");
+    }
+}
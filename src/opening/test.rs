@@ -0,0 +1,38 @@
+use super::*;
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[test]
+fn every_line_of_the_table_is_legal() {
+    // Building the table panics on a bad line
+    assert!(table().len() > 100);
+}
+
+#[test]
+fn names_the_deepest_known_position() {
+    let opening = classify(START, "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6 Be3 e5").unwrap();
+
+    assert_eq!(
+        opening,
+        &Opening {
+            eco: "B90",
+            name: "Sicilian Defense: Najdorf Variation"
+        }
+    );
+}
+
+#[test]
+fn recognises_transpositions() {
+    // The Scotch Game reached through the Three Knights move order
+    let opening = classify(START, "Nf3 Nc6 e4 e5 d4 exd4 Nxd4").unwrap();
+
+    assert_eq!(opening.eco, "C45");
+    assert_eq!(opening.name, "Scotch Game");
+}
+
+#[test]
+fn unknown_games_have_no_opening() {
+    assert_eq!(classify(START, ""), None);
+    assert_eq!(classify("8/8/8/4k3/8/8/8/4K2R w K - 0 1", "Rh5+"), None);
+    assert_eq!(classify("not a position", "e4"), None);
+}
@@ -0,0 +1,281 @@
+//! Reading games in Portable Game Notation
+
+use std::str::FromStr;
+
+use chess::Board;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::notation;
+
+#[cfg(test)]
+mod test;
+
+/// A game as written, moves not checked yet
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Game {
+    pub(crate) tags: Vec<(String, String)>,
+    /// Mainline moves in SAN
+    pub(crate) moves: Vec<String>,
+    /// Game termination marker after the moves
+    pub(crate) termination: Option<String>,
+}
+
+impl Game {
+    pub(crate) fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// `Date` tag, with unknown months and days taken as the first one
+    pub(crate) fn date(&self) -> Option<NaiveDate> {
+        let mut parts = self.tag("Date")?.split('.');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next().and_then(|m| m.parse().ok()).unwrap_or(1);
+        let day = parts.next().and_then(|d| d.parse().ok()).unwrap_or(1);
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    /// Result of a finished game, from the termination marker or the
+    /// `Result` tag
+    pub(crate) fn result(&self) -> Option<&str> {
+        [self.termination.as_deref(), self.tag("Result")]
+            .into_iter()
+            .flatten()
+            .find(|result| ["1-0", "0-1", "1/2-1/2"].contains(result))
+    }
+
+    /// Check every move from the `FEN` tag or the initial position
+    pub(crate) fn replay(&self) -> Result<Replayed, Invalid> {
+        let start = match self.tag("FEN") {
+            Some(fen) => Board::from_str(fen).map_err(|_| Invalid::Fen {
+                fen: fen.to_string(),
+            })?,
+            None => Board::default(),
+        };
+
+        Ok(Replayed {
+            start,
+            moves: replay(start, &self.moves)?,
+        })
+    }
+}
+
+/// Check `moves` from `start`, rewriting them in the standard form
+pub(crate) fn replay<S: AsRef<str>>(start: Board, moves: &[S]) -> Result<Vec<String>, Invalid> {
+    let mut board = start;
+    let mut standard = Vec::with_capacity(moves.len());
+    for (ply, san) in moves.iter().enumerate() {
+        let cmove = notation::parse_san(&board, san.as_ref()).map_err(|_| Invalid::Move {
+            ply: ply + 1,
+            san: san.as_ref().to_string(),
+        })?;
+        standard.push(notation::san(&board, cmove));
+        board = board.make_move_new(cmove);
+    }
+
+    Ok(standard)
+}
+
+/// Lines of movetext are kept under this many characters
+const LINE_WIDTH: usize = 80;
+
+/// Write a game starting at `start_pos`. `SetUp` and `FEN` tags are added
+/// after `tags` unless it is the initial position.
+pub(crate) fn write(
+    tags: &[(&str, &str)],
+    start_pos: &str,
+    moves: &[String],
+    result: &str,
+) -> String {
+    let mut pgn = String::new();
+    for (name, value) in tags {
+        let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+        pgn.push_str(&format!("[{name} \"{value}\"]\n"));
+    }
+    if start_pos != Board::default().to_string() {
+        pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{start_pos}\"]\n"));
+    }
+    pgn.push('\n');
+
+    // Numbering goes on from the position's move number and side to move
+    let mut fields = start_pos.split_whitespace().skip(1);
+    let mut white = fields.next() != Some("b");
+    let mut number: usize = fields.nth(3).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+    let mut tokens = Vec::with_capacity(moves.len() * 3 / 2 + 1);
+    for (ply, san) in moves.iter().enumerate() {
+        if white {
+            tokens.push(format!("{number}."));
+        } else if ply == 0 {
+            tokens.push(format!("{number}..."));
+        }
+        tokens.push(san.clone());
+        if !white {
+            number += 1;
+        }
+        white = !white;
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push_str("\n\n");
+
+    pgn
+}
+
+/// Moves of a game checked to be legal
+#[derive(Debug)]
+pub(crate) struct Replayed {
+    pub(crate) start: Board,
+    /// Rewritten in the standard form, without annotations
+    pub(crate) moves: Vec<String>,
+}
+
+/// Why a game can not be imported
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "error", rename_all = "snake_case")]
+pub(crate) enum Invalid {
+    Fen {
+        fen: String,
+    },
+    /// The first move that is illegal, `ply` counting from 1
+    Move {
+        ply: usize,
+        san: String,
+    },
+    MissingTag {
+        tag: &'static str,
+    },
+    /// Unfinished games can not be imported
+    NoResult,
+    NotAPlayer,
+    /// The opponent is a member who blocked the user, or was blocked
+    Blocked {
+        username: String,
+    },
+}
+
+/// Split a file into games. Comments, variations, NAGs and move numbers
+/// are skipped.
+pub(crate) fn parse(text: &str) -> Vec<Game> {
+    let mut games = Vec::new();
+    let mut game = Game::default();
+    let mut chars = text.chars().peekable();
+    let mut variations = 0;
+    let mut line_start = true;
+
+    while let Some(c) = chars.next() {
+        let at_line_start = line_start;
+        line_start = c == '\n';
+
+        match c {
+            // Rest of line comments and escaped lines
+            ';' => skip_line(&mut chars, &mut line_start),
+            '%' if at_line_start => skip_line(&mut chars, &mut line_start),
+            '{' => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            '(' => variations += 1,
+            ')' => variations = (variations - 1).max(0),
+            '[' if variations == 0 => {
+                // Tags after moves belong to the next game
+                if !game.moves.is_empty() || game.termination.is_some() {
+                    games.push(std::mem::take(&mut game));
+                }
+                if let Some(tag) = tag(&mut chars) {
+                    game.tags.push(tag);
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = String::from(c);
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || "[]{}();".contains(next) {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                if variations > 0 {
+                    continue;
+                }
+
+                match token.as_str() {
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        game.termination = Some(token);
+                        games.push(std::mem::take(&mut game));
+                    }
+                    _ if token.starts_with('$') => {}
+                    _ => {
+                        // Move numbers, possibly glued to the move: `12.Nf3`.
+                        // Digits without dots are a move, castling as `0-0`.
+                        let rest = token.trim_start_matches(|c: char| c.is_ascii_digit());
+                        let san = if rest.len() < token.len() && rest.starts_with('.') {
+                            rest.trim_start_matches('.')
+                        } else {
+                            token.as_str()
+                        };
+                        if !san.is_empty() {
+                            game.moves.push(san.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if game != Game::default() {
+        games.push(game);
+    }
+
+    games
+}
+
+fn skip_line(chars: &mut impl Iterator<Item = char>, line_start: &mut bool) {
+    for c in chars {
+        if c == '\n' {
+            *line_start = true;
+            break;
+        }
+    }
+}
+
+/// Read `Name "value"]` after the opening bracket
+fn tag(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<(String, String)> {
+    let mut name = String::new();
+    let mut value = String::new();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => quoted = false,
+            '"' => quoted = true,
+            '\\' if quoted => value.extend(chars.next()),
+            ']' if !quoted => break,
+            c if quoted => value.push(c),
+            c if !c.is_whitespace() && value.is_empty() => name.push(c),
+            _ => {}
+        }
+    }
+
+    (!name.is_empty()).then_some((name, value))
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+const TWO_GAMES: &str = r#"[Event "Club championship"]
+[Site "?"]
+[Date "2023.10.05"]
+[White "alice"]
+[Black "bob"]
+[Result "1-0"]
+
+1. e4 e5 2. Bc4 {Italian style} Nc6 3. Qh5?! (3. Nf3 Nf6 (3... Bc5) 4. d3) 3...
+Nf6?? $4 4. Qxf7# 1-0
+
+; Second round
+[White "bob"]
+[Black "alice"]
+[Result "1/2-1/2"]
+[Date "2023.??.??"]
+
+1.d4 d5 2.c4 e6
+% skipped line 3. e4
+1/2-1/2
+"#;
+
+fn moves(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_string).collect()
+}
+
+#[test]
+fn splits_games_and_skips_noise() {
+    let games = parse(TWO_GAMES);
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves, moves("e4 e5 Bc4 Nc6 Qh5?! Nf6?? Qxf7#"));
+    assert_eq!(games[0].tag("Event"), Some("Club championship"));
+    assert_eq!(games[0].result(), Some("1-0"));
+    assert_eq!(games[1].moves, moves("d4 d5 c4 e6"));
+    assert_eq!(games[1].tag("White"), Some("bob"));
+    assert_eq!(games[1].result(), Some("1/2-1/2"));
+}
+
+#[test]
+fn keeps_castling_written_with_zeros() {
+    let games = parse("1. e4 e5 2. Nf3 Nf6 3. Bc4 Bc5 4.0-0 0-0 5. d3 *");
+
+    assert_eq!(games[0].moves, moves("e4 e5 Nf3 Nf6 Bc4 Bc5 0-0 0-0 d3"));
+    assert_eq!(
+        games[0].replay().unwrap().moves,
+        moves("e4 e5 Nf3 Nf6 Bc4 Bc5 O-O O-O d3")
+    );
+}
+
+#[test]
+fn reads_dates() {
+    let games = parse(TWO_GAMES);
+
+    assert_eq!(games[0].date(), NaiveDate::from_ymd_opt(2023, 10, 5));
+    assert_eq!(games[1].date(), NaiveDate::from_ymd_opt(2023, 1, 1));
+    assert_eq!(Game::default().date(), None);
+}
+
+#[test]
+fn reads_escaped_tags() {
+    let games = parse(r#"[Event "The \"big\" one"] [Result "*"] *"#);
+
+    assert_eq!(games[0].tag("Event"), Some(r#"The "big" one"#));
+    assert_eq!(games[0].result(), None);
+}
+
+#[test]
+fn games_without_termination_are_kept() {
+    let games = parse("1. e4 e5\n[White \"x\"]\n1. d4");
+
+    assert_eq!(games.len(), 2);
+    assert_eq!(games[0].moves, moves("e4 e5"));
+    assert_eq!(games[1].moves, moves("d4"));
+    assert_eq!(games[1].termination, None);
+}
+
+#[test]
+fn replays_into_standard_moves() {
+    let games = parse(TWO_GAMES);
+    let replayed = games[0].replay().unwrap();
+
+    assert_eq!(replayed.start, Board::default());
+    assert_eq!(replayed.moves, moves("e4 e5 Bc4 Nc6 Qh5 Nf6 Qxf7#"));
+}
+
+#[test]
+fn reports_the_offending_ply() {
+    let games = parse("1. e4 e5 2. Ke3 Nc6 *");
+
+    assert_eq!(
+        games[0].replay().unwrap_err(),
+        Invalid::Move {
+            ply: 3,
+            san: "Ke3".to_string()
+        }
+    );
+}
+
+#[test]
+fn starts_from_the_fen_tag() {
+    let games = parse(r#"[FEN "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1"] 1. Ra8# 1-0"#);
+    assert_eq!(games[0].replay().unwrap().moves, moves("Ra8#"));
+
+    let games = parse(r#"[FEN "not a position"] 1. e4 *"#);
+    assert!(matches!(games[0].replay(), Err(Invalid::Fen { .. })));
+}
+
+#[test]
+fn written_games_parse_back() {
+    let text = write(
+        &[("White", "alice"), ("Black", "bob \"the\" rook")],
+        &Board::default().to_string(),
+        &moves("e4 e5 Bc4 Nc6 Qh5 Nf6 Qxf7#"),
+        "1-0",
+    );
+
+    assert!(text.contains("1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0"));
+    assert!(!text.contains("FEN"));
+
+    let games = parse(&text);
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0].tag("Black"), Some("bob \"the\" rook"));
+    assert_eq!(games[0].moves, moves("e4 e5 Bc4 Nc6 Qh5 Nf6 Qxf7#"));
+    assert_eq!(games[0].termination.as_deref(), Some("1-0"));
+}
+
+#[test]
+fn numbering_follows_the_start_position() {
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 3 7";
+    let text = write(&[], fen, &moves("Nf6 Nc3 Bc5"), "*");
+
+    assert!(text.contains(&format!("[SetUp \"1\"]\n[FEN \"{fen}\"]\n")));
+    assert!(text.contains("7... Nf6 8. Nc3 Bc5 *"));
+}
+
+#[test]
+fn long_games_are_wrapped() {
+    let long = moves(&"Nf3 Nf6 Ng1 Ng8 ".repeat(20));
+    let text = write(&[], &Board::default().to_string(), &long, "*");
+
+    assert!(text.lines().all(|line| line.len() <= LINE_WIDTH));
+    assert_eq!(parse(&text)[0].moves, long);
+}
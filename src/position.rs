@@ -0,0 +1,140 @@
+//! Index of the positions reached in finished games, used to search games
+//! by position and by the opening explorer
+
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use chess::Board;
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+
+use crate::notation;
+
+#[cfg(test)]
+mod test;
+
+/// Games indexed per transaction when catching up at startup
+const BACKFILL_BATCH: i64 = 100;
+
+/// FEN without the move counters, so transpositions share a key
+pub(crate) fn key(board: &Board) -> String {
+    board
+        .to_string()
+        .split_whitespace()
+        .take(4)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keys of every position from `start` on, each with the move played from
+/// it in standard SAN. Stops at the first move that does not parse.
+pub(crate) fn positions(start: Board, moves: &str) -> Vec<(String, Option<String>)> {
+    let mut board = start;
+    let mut positions = Vec::new();
+    for san in moves.split_whitespace() {
+        let Ok(cmove) = notation::parse_san(&board, san) else {
+            break;
+        };
+        positions.push((key(&board), Some(notation::san(&board, cmove))));
+        board = board.make_move_new(cmove);
+    }
+    positions.push((key(&board), None));
+    positions
+}
+
+/// Store the positions of finished game `id`
+pub(crate) async fn index(
+    conn: &mut PgConnection,
+    id: i64,
+    start_pos: &str,
+    moves: &str,
+) -> Result<(), StatusCode> {
+    let Ok(start) = Board::from_str(start_pos) else {
+        error!("Game {id} starts from an invalid position");
+        return Ok(());
+    };
+
+    let (keys, next): (Vec<String>, Vec<Option<String>>) =
+        positions(start, moves).into_iter().unzip();
+    let plies: Vec<i32> = (0..keys.len() as i32).collect();
+
+    sqlx::query!(
+        "
+        INSERT INTO games.t_positions(id_game, ply, position_key, next_san)
+        SELECT $1, *
+        FROM UNNEST($2::int[], $3::text[], $4::text[])
+        ON CONFLICT DO NOTHING
+        ",
+        id,
+        &plies,
+        &keys,
+        &next as &[Option<String>],
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error indexing positions {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+/// Index the games finished before positions were stored
+pub(crate) async fn backfill(pool: PgPool) {
+    let mut last = 0;
+    let mut indexed = 0;
+    loop {
+        let games = sqlx::query!(
+            "
+            SELECT id, start_pos, moves
+            FROM games.t_finished fi
+            WHERE id > $1
+              AND NOT EXISTS (
+                SELECT
+                FROM games.t_positions po
+                WHERE po.id_game = fi.id
+              )
+            ORDER BY id
+            LIMIT $2
+            ",
+            last,
+            BACKFILL_BATCH,
+        )
+        .fetch_all(&pool)
+        .await;
+        let games = match games {
+            Ok(games) if games.is_empty() => break,
+            Ok(games) => games,
+            Err(err) => {
+                error!("Error getting games to index {err}");
+                return;
+            }
+        };
+
+        let result = async {
+            let mut trx = pool.begin().await.map_err(|err| {
+                error!("Error starting transaction {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+            for game in &games {
+                index(&mut trx, game.id, &game.start_pos, &game.moves).await?;
+            }
+            trx.commit().await.map_err(|err| {
+                error!("Error commiting transaction {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+        }
+        .await;
+        if result.is_err() {
+            return;
+        }
+
+        indexed += games.len();
+        last = games.last().map_or(last, |game| game.id);
+    }
+
+    if indexed > 0 {
+        info!("Indexed the positions of {indexed} games");
+    }
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn keys_ignore_move_counters() {
+    let early = Board::from_str("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+    let late = Board::from_str("4k3/8/8/8/8/8/8/4K2R w K - 37 80").unwrap();
+
+    assert_eq!(key(&early), key(&late));
+    assert_eq!(key(&early), "4k3/8/8/8/8/8/8/4K2R w K -");
+}
+
+#[test]
+fn transpositions_share_keys() {
+    let one = positions(Board::default(), "e4 e5 Nf3 Nc6");
+    let other = positions(Board::default(), "Nf3 Nc6 e4 e5");
+
+    assert_eq!(one.len(), 5);
+    assert_eq!(one[4].0, other[4].0);
+    assert_ne!(one[2].0, other[2].0);
+}
+
+#[test]
+fn next_moves_are_standard() {
+    let positions = positions(Board::default(), "e2e4 e7e5 Ng1f3");
+
+    assert_eq!(positions[0].0, key(&Board::default()));
+    let next: Vec<_> = positions.iter().map(|p| p.1.as_deref()).collect();
+    assert_eq!(next, [Some("e4"), Some("e5"), Some("Nf3"), None]);
+}
+
+#[test]
+fn stops_at_unreadable_moves() {
+    let positions = positions(Board::default(), "e4 Ke3 Nf3");
+
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[1].1, None);
+}
@@ -0,0 +1,97 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Status {
+    Online,
+    InGame,
+    Offline,
+}
+
+/// Users that left are forgotten once this many are tracked
+const MAX_TRACKED: usize = 100_000;
+
+/// Last time each user was seen making an authenticated request, and the
+/// real-time connections each one holds open
+pub(crate) struct Presence {
+    timeout: Duration,
+    last_seen: Mutex<HashMap<i64, Instant>>,
+    connections: Mutex<HashMap<i64, usize>>,
+}
+
+/// Keeps its user online until dropped
+pub(crate) struct Connection {
+    presence: Arc<Presence>,
+    user_id: i64,
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let mut connections = self
+            .presence
+            .connections
+            .lock()
+            .expect("presence mutex poisoned");
+        if let Some(count) = connections.get_mut(&self.user_id) {
+            *count -= 1;
+            if *count == 0 {
+                connections.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+impl Presence {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            last_seen: Mutex::new(HashMap::new()),
+            connections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn connect(self: &Arc<Self>, user_id: i64) -> Connection {
+        let mut connections = self.connections.lock().expect("presence mutex poisoned");
+        *connections.entry(user_id).or_default() += 1;
+        Connection {
+            presence: self.clone(),
+            user_id,
+        }
+    }
+
+    pub(crate) fn seen(&self, user_id: i64) {
+        let now = Instant::now();
+        let mut last_seen = self.last_seen.lock().expect("presence mutex poisoned");
+        if last_seen.len() >= MAX_TRACKED {
+            last_seen.retain(|_, seen| now.duration_since(*seen) < self.timeout);
+        }
+        last_seen.insert(user_id, now);
+    }
+
+    /// Status of a user given whether it has an active game
+    pub(crate) fn status(&self, user_id: i64, in_game: bool) -> Status {
+        let connected = self
+            .connections
+            .lock()
+            .expect("presence mutex poisoned")
+            .contains_key(&user_id);
+        let recent = self
+            .last_seen
+            .lock()
+            .expect("presence mutex poisoned")
+            .get(&user_id)
+            .is_some_and(|seen| seen.elapsed() < self.timeout);
+
+        match (connected || recent, in_game) {
+            (false, _) => Status::Offline,
+            (true, true) => Status::InGame,
+            (true, false) => Status::Online,
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! Training puzzles: a position with a single winning line. Solutions are
+//! stored in UCI as in the Lichess puzzle database, the first move is the
+//! opponent's and sets the puzzle up.
+
+use std::str::FromStr;
+
+use chess::{Board, BoardStatus, ChessMove};
+use sqlx::PgPool;
+use tokio::{
+    fs::File,
+    io::{AsyncBufReadExt, BufReader},
+};
+use tracing::{error, info};
+
+use crate::notation;
+
+#[cfg(test)]
+mod test;
+
+/// Puzzles inserted per query while importing
+const IMPORT_BATCH: usize = 1000;
+
+/// Where each field is in a line of the CSV file
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Columns {
+    id: usize,
+    fen: usize,
+    moves: usize,
+    rating: usize,
+    themes: Option<usize>,
+}
+
+impl Columns {
+    /// Columns named by the header, or those of the Lichess database when
+    /// the file has no header
+    pub(crate) fn from_header(line: &str) -> (Self, bool) {
+        let names: Vec<String> = line
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .collect();
+        let find = |wanted: &[&str]| {
+            names
+                .iter()
+                .position(|name| wanted.contains(&name.as_str()))
+        };
+
+        match (
+            find(&["puzzleid", "id"]),
+            find(&["fen"]),
+            find(&["moves", "solution"]),
+            find(&["rating"]),
+        ) {
+            (Some(id), Some(fen), Some(moves), Some(rating)) => (
+                Columns {
+                    id,
+                    fen,
+                    moves,
+                    rating,
+                    themes: find(&["themes"]),
+                },
+                true,
+            ),
+            _ => (
+                Columns {
+                    id: 0,
+                    fen: 1,
+                    moves: 2,
+                    rating: 3,
+                    themes: Some(7),
+                },
+                false,
+            ),
+        }
+    }
+
+    /// Read a puzzle, if its line is complete and its moves legal
+    pub(crate) fn row(&self, line: &str) -> Option<Row> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let row = Row {
+            id: fields.get(self.id)?.to_string(),
+            fen: fields.get(self.fen)?.to_string(),
+            moves: fields.get(self.moves)?.to_string(),
+            rating: fields.get(self.rating)?.parse().ok()?,
+            themes: self
+                .themes
+                .and_then(|themes| fields.get(themes))
+                .unwrap_or(&"")
+                .to_string(),
+        };
+
+        (!row.id.is_empty() && Puzzle::new(&row.fen, &row.moves).is_some()).then_some(row)
+    }
+}
+
+/// A line of the CSV file
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Row {
+    pub(crate) id: String,
+    pub(crate) fen: String,
+    pub(crate) moves: String,
+    pub(crate) rating: i32,
+    /// Space separated
+    pub(crate) themes: String,
+}
+
+/// What a move of the solver leads to
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Step {
+    Wrong,
+    /// The opponent answered with `reply`, the solver goes on at `ply`
+    Correct {
+        reply: String,
+        ply: usize,
+    },
+    Solved,
+}
+
+#[derive(Debug)]
+pub(crate) struct Puzzle {
+    start: Board,
+    solution: Vec<ChessMove>,
+}
+
+impl Puzzle {
+    /// Puzzle from `fen` and its solution in UCI. The solver must make the
+    /// last move, so the solution has an even number of moves.
+    pub(crate) fn new(fen: &str, moves: &str) -> Option<Self> {
+        let start = Board::from_str(fen).ok()?;
+        let mut board = start;
+        let mut solution = Vec::new();
+        for uci in moves.split_whitespace() {
+            let cmove = ChessMove::from_str(uci).ok()?;
+            if !board.legal(cmove) {
+                return None;
+            }
+            board = board.make_move_new(cmove);
+            solution.push(cmove);
+        }
+
+        (!solution.is_empty() && solution.len() % 2 == 0).then_some(Self { start, solution })
+    }
+
+    /// Board once the first `ply` moves of the solution are played
+    pub(crate) fn board(&self, ply: usize) -> Board {
+        self.solution[..ply.min(self.solution.len())]
+            .iter()
+            .fold(self.start, |board, cmove| board.make_move_new(*cmove))
+    }
+
+    /// SAN of the opponent's move leading to `ply`, the one setting the
+    /// puzzle up at first
+    pub(crate) fn last_move(&self, ply: usize) -> String {
+        let ply = ply.clamp(1, self.solution.len());
+        notation::san(&self.board(ply - 1), self.solution[ply - 1])
+    }
+
+    /// Rest of the solution from `ply` in SAN
+    pub(crate) fn line(&self, ply: usize) -> Vec<String> {
+        let mut board = self.board(ply);
+        let mut line = Vec::new();
+        for cmove in self.solution.iter().skip(ply) {
+            line.push(notation::san(&board, *cmove));
+            board = board.make_move_new(*cmove);
+        }
+        line
+    }
+
+    /// Check `cmove` as the solver's move at `ply`. Any mate is accepted on
+    /// the last move, even one the solution does not give.
+    pub(crate) fn play(&self, ply: usize, cmove: ChessMove) -> Step {
+        let board = self.board(ply);
+        let Some(&expected) = self.solution.get(ply) else {
+            return Step::Wrong;
+        };
+        let last = ply + 1 == self.solution.len();
+
+        if last
+            && board.legal(cmove)
+            && board.make_move_new(cmove).status() == BoardStatus::Checkmate
+        {
+            return Step::Solved;
+        }
+        if cmove != expected {
+            return Step::Wrong;
+        }
+        if last {
+            return Step::Solved;
+        }
+
+        let after = board.make_move_new(cmove);
+        Step::Correct {
+            reply: notation::san(&after, self.solution[ply + 1]),
+            ply: ply + 2,
+        }
+    }
+}
+
+/// Add the puzzles of a CSV file, updating those already known
+pub(crate) async fn import(pool: PgPool, path: String) {
+    let file = match File::open(&path).await {
+        Ok(file) => file,
+        Err(err) => {
+            error!("Error opening puzzles file {path}: {err}");
+            return;
+        }
+    };
+
+    let mut lines = BufReader::new(file).lines();
+    let mut columns = None;
+    let mut batch = Vec::with_capacity(IMPORT_BATCH);
+    let (mut imported, mut skipped) = (0, 0);
+    loop {
+        let line = match lines.next_line().await {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Error reading puzzles file {err}");
+                return;
+            }
+        };
+
+        if let Some(line) = &line {
+            let columns = match &columns {
+                Some(columns) => columns,
+                None => {
+                    let (found, header) = Columns::from_header(line);
+                    let columns = columns.insert(found);
+                    if header {
+                        continue;
+                    }
+                    columns
+                }
+            };
+            match columns.row(line) {
+                Some(row) => batch.push(row),
+                None => skipped += 1,
+            }
+        }
+
+        if batch.len() == IMPORT_BATCH || (line.is_none() && !batch.is_empty()) {
+            if let Err(err) = insert(&pool, &batch).await {
+                error!("Error importing puzzles {err}");
+                return;
+            }
+            imported += batch.len();
+            batch.clear();
+        }
+
+        if line.is_none() {
+            break;
+        }
+    }
+
+    info!("Imported {imported} puzzles, skipped {skipped} lines");
+}
+
+async fn insert(pool: &PgPool, rows: &[Row]) -> Result<(), sqlx::Error> {
+    let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+    let fens: Vec<&str> = rows.iter().map(|row| row.fen.as_str()).collect();
+    let moves: Vec<&str> = rows.iter().map(|row| row.moves.as_str()).collect();
+    let ratings: Vec<i32> = rows.iter().map(|row| row.rating).collect();
+    let themes: Vec<&str> = rows.iter().map(|row| row.themes.as_str()).collect();
+
+    sqlx::query!(
+        "
+        INSERT INTO games.t_puzzles(id, fen, moves, rating, themes)
+        SELECT id, fen, moves, rating, COALESCE(string_to_array(NULLIF(themes, ''), ' '), '{}')
+        FROM UNNEST($1::text[], $2::text[], $3::text[], $4::int[], $5::text[])
+            AS p(id, fen, moves, rating, themes)
+        ON CONFLICT (id) DO UPDATE
+        SET fen = EXCLUDED.fen,
+            moves = EXCLUDED.moves,
+            rating = EXCLUDED.rating,
+            themes = EXCLUDED.themes
+        ",
+        &ids as &[&str],
+        &fens as &[&str],
+        &moves as &[&str],
+        &ratings,
+        &themes as &[&str],
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use super::*;
+
+const HEADER: &str =
+    "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags";
+const MATE_IN_TWO: &str = "00sHx,q3k1nr/1pp1nQpp/3p4/1P2p3/4P3/B1PP1b2/B5PP/5K2 b k - 0 17,e8d7 a2e6 d7d8 f7f8,1760,80,83,72,mate mateIn2 middlegame short,https://lichess.org/yyznGmXs/black#34,Italian_Game";
+
+fn mate_in_two() -> Puzzle {
+    let row = Columns::from_header(HEADER).0.row(MATE_IN_TWO).unwrap();
+    Puzzle::new(&row.fen, &row.moves).unwrap()
+}
+
+fn uci(text: &str) -> ChessMove {
+    ChessMove::from_str(text).unwrap()
+}
+
+#[test]
+fn reads_lichess_lines() {
+    let (columns, header) = Columns::from_header(HEADER);
+    assert!(header);
+
+    let row = columns.row(MATE_IN_TWO).unwrap();
+    assert_eq!(row.id, "00sHx");
+    assert_eq!(row.moves, "e8d7 a2e6 d7d8 f7f8");
+    assert_eq!(row.rating, 1760);
+    assert_eq!(row.themes, "mate mateIn2 middlegame short");
+}
+
+#[test]
+fn files_without_header_use_the_lichess_layout() {
+    let (columns, header) = Columns::from_header(MATE_IN_TWO);
+
+    assert!(!header);
+    assert_eq!(columns, Columns::from_header(HEADER).0);
+}
+
+#[test]
+fn other_layouts_are_read_from_the_header() {
+    let (columns, _) = Columns::from_header("fen,rating,moves,id");
+    let row = columns
+        .row("6k1/5ppp/8/8/8/8/5PPP/R5K1 b - - 0 1,900,g8f8 a1a8,mate1")
+        .unwrap();
+
+    assert_eq!(row.id, "mate1");
+    assert_eq!(row.rating, 900);
+    assert_eq!(row.themes, "");
+}
+
+#[test]
+fn rejects_broken_lines() {
+    let columns = Columns::from_header(HEADER).0;
+
+    // Illegal move
+    assert_eq!(columns.row(&MATE_IN_TWO.replace("e8d7", "e8e7")), None);
+    // Ends with the opponent's move
+    assert_eq!(columns.row(&MATE_IN_TWO.replace(" f7f8", "")), None);
+    assert_eq!(columns.row("00sHx,not a fen,e2e4 e7e5,1500"), None);
+    assert_eq!(columns.row("00sHx"), None);
+}
+
+#[test]
+fn solves_move_by_move() {
+    let puzzle = mate_in_two();
+    assert_eq!(puzzle.last_move(1), "Kd7");
+
+    assert_eq!(
+        puzzle.play(1, uci("a2e6")),
+        Step::Correct {
+            reply: "Kd8".to_string(),
+            ply: 3
+        }
+    );
+    assert_eq!(puzzle.last_move(3), "Kd8");
+    assert_eq!(puzzle.play(3, uci("f7f8")), Step::Solved);
+}
+
+#[test]
+fn shows_the_rest_of_the_solution() {
+    let puzzle = mate_in_two();
+
+    assert_eq!(puzzle.line(1), ["Be6+", "Kd8", "Qf8#"]);
+    assert_eq!(puzzle.line(3), ["Qf8#"]);
+    assert!(puzzle.line(4).is_empty());
+}
+
+#[test]
+fn other_moves_are_wrong() {
+    let puzzle = mate_in_two();
+
+    assert_eq!(puzzle.play(1, uci("f7g7")), Step::Wrong);
+    // Not even legal
+    assert_eq!(puzzle.play(1, uci("a2a8")), Step::Wrong);
+    assert_eq!(puzzle.play(5, uci("f7f8")), Step::Wrong);
+}
+
+#[test]
+fn any_mate_solves_the_last_move() {
+    // Both rooks mate on the back rank
+    let puzzle = Puzzle::new("6k1/5ppp/8/8/8/8/5PPP/RR4K1 b - - 0 1", "g8h8 a1a8").unwrap();
+
+    assert_eq!(puzzle.play(1, uci("b1b8")), Step::Solved);
+    assert_eq!(puzzle.play(1, uci("b1b7")), Step::Wrong);
+}
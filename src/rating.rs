@@ -0,0 +1,13 @@
+#[cfg(test)]
+mod test;
+
+const K_FACTOR: f64 = 32.0;
+
+/// Elo update for a single game. `score` is from `a`'s point of view
+/// (1.0 win, 0.5 draw, 0.0 loss). Returns the new ratings of `a` and `b`.
+pub(crate) fn elo(a: i32, b: i32, score: f64) -> (i32, i32) {
+    let expected = 1.0 / (1.0 + 10f64.powf(f64::from(b - a) / 400.0));
+    let delta = (K_FACTOR * (score - expected)).round() as i32;
+
+    (a + delta, b - delta)
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+#[test]
+fn equal_ratings() {
+    assert_eq!(elo(1500, 1500, 1.0), (1516, 1484));
+    assert_eq!(elo(1500, 1500, 0.0), (1484, 1516));
+    assert_eq!(elo(1500, 1500, 0.5), (1500, 1500));
+}
+
+#[test]
+fn upset_is_worth_more() {
+    let (low, _) = elo(1200, 1800, 1.0);
+    let (high, _) = elo(1800, 1200, 1.0);
+    assert!(low - 1200 > high - 1800);
+}
+
+#[test]
+fn rating_is_conserved() {
+    for (a, b, score) in [(1500, 1700, 1.0), (2100, 900, 0.5), (1234, 1432, 0.0)] {
+        let (na, nb) = elo(a, b, score);
+        assert_eq!(na + nb, a + b);
+    }
+}
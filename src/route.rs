@@ -1,2 +1,12 @@
+pub mod arena;
+pub mod bot;
+pub mod evaluate;
+pub mod friends;
 pub mod game;
+pub mod leaderboard;
+pub mod position;
+pub mod puzzle;
+pub mod spectate;
+pub mod team;
+pub mod tournament;
 pub mod user;
@@ -0,0 +1,15 @@
+//! Arena tournaments, where players are paired again as soon as they finish
+
+mod berserk;
+mod create;
+mod get;
+mod join;
+mod leave;
+mod list;
+
+pub(crate) use berserk::handler as berserk;
+pub(crate) use create::handler as create;
+pub(crate) use get::handler as get;
+pub(crate) use join::handler as join;
+pub(crate) use leave::handler as leave;
+pub(crate) use list::handler as list;
@@ -0,0 +1,104 @@
+use crate::{
+    authentication::LoggedUser,
+    hub::{GameEvent, Hub},
+};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Halve our own clock in an arena game for an extra point on a win. Only
+/// before making a move.
+#[tracing::instrument(skip(hub))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Berserk>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Going berserk");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Locking the game keeps moves out until the choice is stored
+    let game = sqlx::query!(
+        r#"
+        SELECT
+            ac.player_w,
+            ac.player_b,
+            ag.berserk_w,
+            ag.berserk_b,
+            (
+                SELECT count(*)
+                FROM games.t_moves mo
+                WHERE mo.id_game = ac.id
+            ) as "moves!"
+        FROM games.t_active ac
+            JOIN games.t_arena_games ag ON ag.id_game = ac.id
+        WHERE ac.id = $1
+        FOR UPDATE OF ac, ag
+        "#,
+        payload.board_id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    // White has no move before the first one, black before the second
+    let (white, berserk, moved) = if &game.player_w == user.username() {
+        (true, game.berserk_w, game.moves > 0)
+    } else if &game.player_b == user.username() {
+        (false, game.berserk_b, game.moves > 1)
+    } else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    if berserk || moved {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arena_games
+        SET berserk_w = berserk_w OR $2,
+            berserk_b = berserk_b OR NOT $2,
+            clock_w = CASE WHEN $2 THEN clock_w / 2 ELSE clock_w END,
+            clock_b = CASE WHEN $2 THEN clock_b ELSE clock_b / 2 END
+        WHERE id_game = $1
+        ",
+        payload.board_id,
+        white,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error going berserk {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    hub.publish(
+        payload.board_id,
+        GameEvent::Berserk {
+            by: user.username().clone(),
+        },
+    );
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Berserk {
+    board_id: i64,
+}
@@ -0,0 +1,73 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::ops::RangeInclusive;
+use tracing::{error, info};
+
+const NAME_LENGTH: RangeInclusive<usize> = 1..=100;
+const MINUTES: RangeInclusive<i32> = 1..=60;
+const INCREMENT: RangeInclusive<i32> = 0..=60;
+/// Length of an arena, in minutes
+const DURATION: RangeInclusive<i64> = 5..=720;
+
+/// Schedule an arena, starting now unless told when. Returns its id.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<NewArena>,
+) -> Result<Json<i64>, StatusCode> {
+    info!("Creating arena");
+
+    let name = payload.name.trim();
+    if !NAME_LENGTH.contains(&name.chars().count())
+        || !MINUTES.contains(&payload.minutes)
+        || !INCREMENT.contains(&payload.increment)
+        || !DURATION.contains(&payload.duration)
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let now = Utc::now();
+    let starts_at = payload.starts_at.unwrap_or(now);
+    if starts_at < now {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let id = sqlx::query_scalar!(
+        "
+        INSERT INTO games.t_arenas(name, organizer, minutes, increment, starts_at, ends_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        ",
+        name,
+        user.username(),
+        payload.minutes,
+        payload.increment,
+        starts_at,
+        starts_at + Duration::minutes(payload.duration),
+    )
+    .fetch_one(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error creating arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Arena {id} created");
+    Ok(Json(id))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct NewArena {
+    name: String,
+    /// Minutes per player
+    minutes: i32,
+    /// Seconds added per move
+    increment: i32,
+    /// Minutes the arena lasts
+    duration: i64,
+    starts_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,169 @@
+use crate::arena::{self, FIRE_STREAK};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// An arena with its standings, live while it runs and final once over,
+/// and its games newest first
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<Json<Arena>, StatusCode> {
+    let details = sqlx::query!(
+        "
+        SELECT name, organizer, minutes, increment, starts_at, ends_at, status
+        FROM games.t_arenas
+        WHERE id = $1
+        ",
+        id,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let players = sqlx::query!(
+        "
+        SELECT username, rating, score, streak, paused, rank
+        FROM games.t_arena_players
+        WHERE arena_id = $1
+        ORDER BY rank, score DESC, rating DESC, id
+        ",
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena players {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let standings = players
+        .into_iter()
+        .enumerate()
+        .map(|(place, player)| Standing {
+            rank: player.rank.unwrap_or(place as i32 + 1),
+            username: player.username,
+            rating: player.rating,
+            score: player.score,
+            on_fire: player.streak >= FIRE_STREAK,
+            paused: player.paused,
+        })
+        .collect();
+
+    let games = sqlx::query!(
+        "
+        SELECT g.id_game, w.username as white, b.username as black, g.berserk_w, g.berserk_b,
+            g.clock_w, g.clock_b, g.turn_started_at, g.result, g.points_w, g.points_b
+        FROM games.t_arena_games g
+            JOIN games.t_arena_players w ON w.id = g.white
+            JOIN games.t_arena_players b ON b.id = g.black
+        WHERE g.arena_id = $1
+        ORDER BY g.id_game DESC
+        ",
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .into_iter()
+    .map(|game| Game {
+        id: game.id_game,
+        white: game.white,
+        black: game.black,
+        clock_w: arena::clock(details.minutes, details.increment, game.berserk_w).into(),
+        clock_b: arena::clock(details.minutes, details.increment, game.berserk_b).into(),
+        berserk_w: game.berserk_w,
+        berserk_b: game.berserk_b,
+        left_w: game.clock_w,
+        left_b: game.clock_b,
+        turn_started_at: game.turn_started_at,
+        result: game.result,
+        points_w: game.points_w,
+        points_b: game.points_b,
+    })
+    .collect();
+
+    Ok(Json(Arena {
+        id,
+        name: details.name,
+        organizer: details.organizer,
+        minutes: details.minutes,
+        increment: details.increment,
+        starts_at: details.starts_at,
+        ends_at: details.ends_at,
+        status: details.status,
+        standings,
+        games,
+    }))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Arena {
+    id: i64,
+    name: String,
+    organizer: String,
+    minutes: i32,
+    increment: i32,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    status: String,
+    standings: Vec<Standing>,
+    games: Vec<Game>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Standing {
+    rank: i32,
+    username: String,
+    rating: i32,
+    score: i32,
+    /// Won enough games in a row for the next points to be doubled
+    on_fire: bool,
+    paused: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Game {
+    id: i64,
+    white: String,
+    black: String,
+    clock_w: Clock,
+    clock_b: Clock,
+    berserk_w: bool,
+    berserk_b: bool,
+    /// Milliseconds left on each clock when the current turn started
+    left_w: i64,
+    left_b: i64,
+    turn_started_at: DateTime<Utc>,
+    result: Option<String>,
+    /// Arena points, missing until scored and for games ending too late
+    points_w: Option<i32>,
+    points_b: Option<i32>,
+}
+
+/// Time control of one player
+#[derive(Serialize)]
+pub(crate) struct Clock {
+    seconds: i32,
+    increment: i32,
+}
+
+impl From<(i32, i32)> for Clock {
+    fn from((seconds, increment): (i32, i32)) -> Self {
+        Self { seconds, increment }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Join an arena before it ends, or come back after pausing
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Joining arena {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let over = sqlx::query_scalar!(
+        r#"
+        SELECT status = 'finished' OR ends_at <= now() as "over!"
+        FROM games.t_arenas
+        WHERE id = $1
+        FOR SHARE
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    if over {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    let joined = sqlx::query!(
+        "
+        INSERT INTO games.t_arena_players(arena_id, user_id, username, rating)
+        SELECT $1, id, username, rating
+        FROM users.basic_info
+        WHERE id = $2
+        ON CONFLICT (arena_id, user_id) DO UPDATE
+        SET paused = false
+        WHERE t_arena_players.paused
+        ",
+        id,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error joining arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if joined.rows_affected() == 0 {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,87 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Leave an arena before it starts, or pause while it runs: the current
+/// game is still to be finished, but no new one is paired. Points are kept.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Leaving arena {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let status = sqlx::query_scalar!(
+        "
+        SELECT status
+        FROM games.t_arenas
+        WHERE id = $1
+        FOR SHARE
+        ",
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let left = match status.as_str() {
+        "open" => {
+            sqlx::query!(
+                "
+                DELETE FROM games.t_arena_players
+                WHERE arena_id = $1
+                  AND user_id = $2
+                ",
+                id,
+                user.id(),
+            )
+            .execute(&mut *trx)
+            .await
+        }
+        "running" => {
+            sqlx::query!(
+                "
+                UPDATE games.t_arena_players
+                SET paused = true
+                WHERE arena_id = $1
+                  AND user_id = $2
+                  AND NOT paused
+                ",
+                id,
+                user.id(),
+            )
+            .execute(&mut *trx)
+            .await
+        }
+        _ => return Err(StatusCode::NOT_ACCEPTABLE),
+    }
+    .map_err(|err| {
+        error!("Error leaving arena {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if left.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
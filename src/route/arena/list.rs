@@ -0,0 +1,54 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Arenas listed
+const LIMIT: i64 = 100;
+
+/// Arenas running or to come, soonest first, then the latest finished
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+) -> Result<Json<Vec<Arena>>, StatusCode> {
+    let arenas = sqlx::query_as!(
+        Arena,
+        r#"
+        SELECT a.id, a.name, a.organizer, a.minutes, a.increment, a.starts_at, a.ends_at,
+            a.status,
+            (
+                SELECT count(*)
+                FROM games.t_arena_players pl
+                WHERE pl.arena_id = a.id
+            ) as "players!"
+        FROM games.t_arenas a
+        ORDER BY a.status = 'finished',
+            CASE WHEN a.status = 'finished' THEN NULL ELSE a.starts_at END,
+            a.ends_at DESC
+        LIMIT $1
+        "#,
+        LIMIT,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(arenas))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Arena {
+    id: i64,
+    name: String,
+    organizer: String,
+    minutes: i32,
+    increment: i32,
+    starts_at: DateTime<Utc>,
+    ends_at: DateTime<Utc>,
+    status: String,
+    players: i64,
+}
@@ -0,0 +1,180 @@
+//! Streaming API for accounts played by programs
+
+use std::{convert::Infallible, sync::Arc, time::Duration};
+
+use crate::{
+    authentication::LoggedUser,
+    hub::{GameEvent, Hub, UserEvent},
+    presence::Presence,
+};
+use axum::{
+    body::StreamBody,
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Result},
+    Extension,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tokio_stream::{
+    wrappers::{BroadcastStream, ReceiverStream},
+    StreamExt, StreamMap,
+};
+use tracing::error;
+
+/// Empty lines are sent this often so proxies keep idle streams open
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Events of a game, tagged with its id
+#[derive(Serialize)]
+struct InGame<'a> {
+    game: i64,
+    #[serde(flatten)]
+    event: &'a GameEvent,
+}
+
+/// Stream of challenges, game starts and changes in the bot's games, one
+/// JSON object per line. Pending challenges and running games are sent
+/// first.
+#[tracing::instrument(skip(hub, presence))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    State(presence): State<Arc<Presence>>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<impl IntoResponse> {
+    let bot = sqlx::query_scalar!(
+        "
+        SELECT bot_account
+        FROM users.basic_info
+        WHERE id = $1
+        ",
+        user.id(),
+    )
+    .fetch_one(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting account {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    if !bot {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    // Subscribe before reading the current state, so nothing falls between
+    let mut user_events = BroadcastStream::new(hub.subscribe_user(user.username()));
+
+    let challenges = sqlx::query!(
+        r#"
+        SELECT inviter as "inviter!", public as "public!", takebacks as "takebacks!"
+        FROM games.v_pending_invites
+        WHERE invited = $1
+          AND NOT EXISTS (
+            SELECT
+            FROM users.blocks bl
+                JOIN users.basic_info u1 ON u1.id = bl.blocker_id
+                JOIN users.basic_info u2 ON u2.id = bl.blocked_id
+            WHERE (u1.username = inviter AND u2.username = invited)
+               OR (u1.username = invited AND u2.username = inviter)
+          )
+        ORDER BY created_at
+        "#,
+        user.username(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting invites {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let active = sqlx::query!(
+        "
+        SELECT id, player_w, player_b, fen
+        FROM games.t_active
+        WHERE player_w = $1
+           OR player_b = $1
+        ORDER BY id
+        ",
+        user.username(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting active games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut games = StreamMap::new();
+    let mut lines = Vec::with_capacity(challenges.len() + active.len());
+    for challenge in challenges {
+        lines.push(line(&UserEvent::Challenge {
+            inviter: challenge.inviter,
+            public: challenge.public,
+            takebacks: challenge.takebacks,
+        }));
+    }
+    for game in active {
+        games.insert(game.id, BroadcastStream::new(hub.subscribe(game.id)));
+        lines.push(line(&UserEvent::GameStart {
+            id: game.id,
+            player_w: game.player_w,
+            player_b: game.player_b,
+            fen: game.fen,
+        }));
+    }
+
+    // The bot stays online for as long as the stream is open
+    let connection = presence.connect(user.id());
+
+    let (sender, receiver) = mpsc::channel::<Result<String, Infallible>>(16);
+    tokio::spawn(async move {
+        let _connection = connection;
+
+        for line in lines {
+            if sender.send(Ok(line)).await.is_err() {
+                return;
+            }
+        }
+
+        let start = tokio::time::Instant::now() + KEEP_ALIVE;
+        let mut keep_alive = tokio::time::interval_at(start, KEEP_ALIVE);
+        loop {
+            let line = tokio::select! {
+                Some(event) = user_events.next() => {
+                    // Lagging streams just miss the events that were dropped
+                    let Ok(event) = event else { continue };
+                    if let UserEvent::GameStart { id, .. } = &event {
+                        games.insert(*id, BroadcastStream::new(hub.subscribe(*id)));
+                    }
+                    line(&event)
+                }
+                Some((game, event)) = games.next(), if !games.is_empty() => {
+                    let Ok(event) = event else { continue };
+                    if let GameEvent::Finished { .. } = &event {
+                        games.remove(&game);
+                    }
+                    line(&InGame { game, event: &event })
+                }
+                _ = keep_alive.tick() => "\n".to_string(),
+            };
+
+            // The client went away
+            if sender.send(Ok(line)).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(ReceiverStream::new(receiver)),
+    ))
+}
+
+fn line<T: Serialize>(event: &T) -> String {
+    let mut line = serde_json::to_string(event).expect("events serialize");
+    line.push('\n');
+    line
+}
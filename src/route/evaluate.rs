@@ -0,0 +1,70 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chess::{Board, BoardStatus};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::{
+    config::Config,
+    notation,
+    uci::{self, Score},
+};
+
+/// Evaluate a position with the external engine
+#[tracing::instrument(skip(config, engines))]
+pub(crate) async fn handler(
+    State(config): State<Arc<Config>>,
+    State(engines): State<Option<Arc<uci::Pool>>>,
+    Query(params): Query<Params>,
+) -> Result<Json<Evaluation>, StatusCode> {
+    let (Some(settings), Some(engines)) = (&config.uci, engines) else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let board = Board::from_str(&params.fen).map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
+    if board.status() != BoardStatus::Ongoing {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let search = engines
+        .search(&board, settings.analysis_depth)
+        .await
+        .map_err(|err| {
+            error!("Error searching with the engine {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Write the variation in SAN, stopping at anything illegal
+    let mut pv = Vec::with_capacity(search.pv.len());
+    let mut position = board;
+    for cmove in search.pv {
+        if !position.legal(cmove) {
+            break;
+        }
+        pv.push(notation::san(&position, cmove));
+        position = position.make_move_new(cmove);
+    }
+
+    Ok(Json(Evaluation {
+        best: notation::san(&board, search.best),
+        score: search.score,
+        pv,
+    }))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Params {
+    fen: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Evaluation {
+    best: String,
+    score: Option<Score>,
+    pv: Vec<String>,
+}
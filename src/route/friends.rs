@@ -0,0 +1,16 @@
+mod accept;
+mod list;
+mod remove;
+mod request;
+
+pub use accept::handler as accept;
+pub use list::handler as list;
+pub use remove::handler as remove;
+pub use request::handler as request;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Friend {
+    username: String,
+}
@@ -0,0 +1,41 @@
+use super::Friend;
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Friend>,
+) -> Result<StatusCode> {
+    info!("Accepting friend request from {}", payload.username);
+
+    let affected = sqlx::query!(
+        "
+        UPDATE users.friends f
+        SET accepted = true
+        FROM users.basic_info u
+        WHERE u.id = f.requester_id
+          AND u.username = $1
+          AND f.addressee_id = $2
+          AND NOT f.accepted
+        ",
+        payload.username,
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error accepting friend request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    Ok(StatusCode::OK)
+}
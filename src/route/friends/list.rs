@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use crate::{
+    authentication::LoggedUser,
+    presence::{Presence, Status},
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+#[tracing::instrument(skip(presence))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(presence): State<Arc<Presence>>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<Json<Friends>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            u.id,
+            u.username,
+            f.accepted,
+            f.requester_id = $1 as "outgoing!",
+            EXISTS (
+                SELECT
+                FROM games.t_active ac
+                WHERE ac.player_w = u.username
+                   OR ac.player_b = u.username
+            ) as "in_game!"
+        FROM users.friends f
+            JOIN users.basic_info u
+              ON u.id = CASE WHEN f.requester_id = $1 THEN f.addressee_id ELSE f.requester_id END
+        WHERE f.requester_id = $1
+           OR f.addressee_id = $1
+        ORDER BY u.username
+        "#,
+        user.id(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting friends {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut friends = Friends::default();
+    for row in rows {
+        if row.accepted {
+            friends.friends.push(FriendStatus {
+                presence: presence.status(row.id, row.in_game),
+                username: row.username,
+            });
+        } else if row.outgoing {
+            friends.outgoing.push(row.username);
+        } else {
+            friends.incoming.push(row.username);
+        }
+    }
+
+    Ok(Json(friends))
+}
+
+#[derive(Serialize, Default)]
+pub struct Friends {
+    friends: Vec<FriendStatus>,
+    /// Requests waiting for the other user
+    outgoing: Vec<String>,
+    /// Requests waiting for us
+    incoming: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct FriendStatus {
+    username: String,
+    presence: Status,
+}
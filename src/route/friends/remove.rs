@@ -0,0 +1,42 @@
+use super::Friend;
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Remove a friend, or decline or cancel a pending request
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Friend>,
+) -> Result<StatusCode> {
+    info!("Removing friend {}", payload.username);
+
+    let affected = sqlx::query!(
+        "
+        DELETE FROM users.friends f
+        USING users.basic_info u
+        WHERE u.username = $1
+          AND (
+            (f.requester_id = $2 AND f.addressee_id = u.id)
+            OR (f.requester_id = u.id AND f.addressee_id = $2)
+          )
+        ",
+        payload.username,
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error removing friend {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    Ok(StatusCode::OK)
+}
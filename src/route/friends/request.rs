@@ -0,0 +1,85 @@
+use super::Friend;
+use crate::{authentication::LoggedUser, social};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Friend>,
+) -> Result<StatusCode> {
+    info!("Sending friend request to {}", payload.username);
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if social::blocked_between(&mut trx, user.username(), &payload.username).await? {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    // Asking someone who already asked us accepts their request
+    let accepted = sqlx::query!(
+        "
+        UPDATE users.friends f
+        SET accepted = true
+        FROM users.basic_info u
+        WHERE u.id = f.requester_id
+          AND u.username = $1
+          AND f.addressee_id = $2
+          AND NOT f.accepted
+        ",
+        payload.username,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error accepting friend request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if accepted == 0 {
+        let affected = sqlx::query!(
+            "
+            INSERT INTO users.friends(requester_id, addressee_id)
+            SELECT $1, id
+            FROM users.basic_info
+            WHERE username = $2
+            ",
+            user.id(),
+            payload.username,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            if err.as_database_error().is_some_and(|err| {
+                matches!(
+                    err.kind(),
+                    ErrorKind::UniqueViolation | ErrorKind::CheckViolation
+                )
+            }) {
+                StatusCode::NOT_ACCEPTABLE
+            } else {
+                error!("Error sending friend request {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(StatusCode::NOT_FOUND.into());
+        }
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
@@ -1,15 +1,53 @@
 mod accept;
+mod accept_import;
+mod accept_takeback;
 mod active;
+mod analysis;
+mod analyze;
+mod decline_import;
+mod decline_takeback;
 mod finished;
 mod get_board;
+mod import_pgn;
+mod imports;
 mod invite;
 mod invited;
 mod make_move;
+mod messages;
+mod premoves;
+mod send_message;
+mod set_premoves;
+mod subscribe;
+mod takeback;
 
 pub use accept::handler as accept;
+pub use accept_import::handler as accept_import;
+pub use accept_takeback::handler as accept_takeback;
 pub use active::handler as active;
+pub use analysis::handler as analysis;
+pub use analyze::handler as analyze;
+pub use decline_import::handler as decline_import;
+pub use decline_takeback::handler as decline_takeback;
 pub use finished::handler as finished;
 pub use get_board::handler as get_board;
+pub use import_pgn::handler as import_pgn;
+pub use imports::handler as imports;
 pub use invite::handler as invite;
 pub use invited::handler as invited;
 pub use make_move::handler as make_move;
+pub use messages::handler as messages;
+pub use premoves::handler as premoves;
+pub use send_message::handler as send_message;
+pub use set_premoves::handler as set_premoves;
+pub use subscribe::handler as subscribe;
+pub use takeback::handler as takeback;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct TakebackRequest {
+    board_id: i64,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ImportRequest {
+    id: i64,
+}
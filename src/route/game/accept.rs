@@ -1,12 +1,19 @@
-use crate::authentication::LoggedUser;
+use crate::{
+    authentication::LoggedUser,
+    game::{self, Event},
+    hub::Hub,
+    social,
+};
 use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
 use serde::Deserialize;
-use sqlx::{error::ErrorKind, PgPool};
+use sqlx::PgPool;
+use std::sync::Arc;
 use tracing::{error, info};
 
-#[tracing::instrument]
+#[tracing::instrument(skip(hub))]
 pub async fn handler(
     State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
     Extension(user): Extension<LoggedUser>,
     Json(payload): Json<Accept>,
 ) -> Result<StatusCode> {
@@ -20,51 +27,39 @@ pub async fn handler(
 
     // Attempt to delete invite
     // If it doesn't delete anything send StatusCode::NOT_FOUND
-    let affected = sqlx::query!(
-        "
+    let invite = sqlx::query!(
+        r#"
         DELETE FROM games.v_pending_invites
         WHERE inviter = $1
           AND invited = $2
-        ",
+        RETURNING public as "public!", takebacks as "takebacks!"
+        "#,
         payload.inviter,
         user.username(),
     )
-    .execute(&mut *trx)
+    .fetch_optional(&mut *trx)
     .await
     .map_err(|err| {
         error!("Error deleting invite {err}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?
-    .rows_affected();
+    .ok_or(StatusCode::NOT_FOUND)?;
 
-    if affected == 0 {
-        return Err(StatusCode::NOT_FOUND.into());
+    if social::blocked_between(&mut trx, &payload.inviter, user.username()).await? {
+        return Err(StatusCode::FORBIDDEN.into());
     }
 
     // Insert a new game as active between this 2 players
     // If there is already one return StatusCode::NOT_ACCEPTABLE
-    sqlx::query!(
-        "
-        INSERT INTO games.t_active(player_w, player_b, fen, start_pos) 
-        VALUES ($1, $2, $3, $3)
-        ",
-        payload.inviter,
+    let (_, start) = game::create(
+        &mut trx,
+        &payload.inviter,
         user.username(),
-        chess::Board::default().to_string(),
+        invite.public,
+        invite.takebacks,
+        Event::Casual,
     )
-    .execute(&mut *trx)
-    .await
-    .map_err(|err| {
-        if err
-            .as_database_error()
-            .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
-        {
-            StatusCode::NOT_ACCEPTABLE
-        } else {
-            error!("Error inserting new game {err}");
-            StatusCode::INTERNAL_SERVER_ERROR
-        }
-    })?;
+    .await?;
 
     // Everything went well, commit the transaction
     trx.commit().await.map_err(|err| {
@@ -72,6 +67,9 @@ pub async fn handler(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    hub.publish_user(&payload.inviter, start.clone());
+    hub.publish_user(user.username(), start);
+
     Ok(StatusCode::OK)
 }
 
@@ -0,0 +1,61 @@
+use super::{import_pgn::Import, ImportRequest};
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Add a game another member imported against the user to both histories
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<ImportRequest>,
+) -> Result<Json<i64>> {
+    info!("Accepting import");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let pending = sqlx::query!(
+        "
+        DELETE FROM games.t_pending_imports
+        WHERE id = $1
+          AND opponent = $2
+        RETURNING player_w, player_b, start_pos, moves, result, end_date
+        ",
+        payload.id,
+        user.username(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error taking pending import {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let id = super::import_pgn::store(
+        &mut trx,
+        &Import {
+            player_w: pending.player_w,
+            player_b: pending.player_b,
+            guest_w: false,
+            guest_b: false,
+            opponent: None,
+            start_pos: pending.start_pos,
+            moves: pending.moves,
+            result: pending.result,
+            date: pending.end_date,
+        },
+    )
+    .await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(id))
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+
+use super::TakebackRequest;
+use crate::{
+    authentication::LoggedUser,
+    hub::{GameEvent, Hub},
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument(skip(hub))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<TakebackRequest>,
+) -> Result<StatusCode> {
+    info!("Accepting takeback");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let request = sqlx::query!(
+        "
+        SELECT
+            tb.requester,
+            tb.move_num,
+            ac.player_w,
+            ac.player_b
+        FROM games.t_takebacks tb
+            JOIN games.t_active ac ON ac.id = tb.id_game
+        WHERE tb.id_game = $1
+        FOR UPDATE
+        ",
+        payload.board_id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting takeback request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    // The opponent of whoever asked has to accept
+    let opponent = if request.requester == request.player_w {
+        &request.player_b
+    } else {
+        &request.player_w
+    };
+    if opponent != user.username() {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_takebacks
+        WHERE id_game = $1
+        ",
+        payload.board_id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting takeback request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Remove the move the request was made on, as long as it is still the last
+    let fen = sqlx::query_scalar!(
+        "
+        DELETE FROM games.t_moves
+        WHERE id_game = $1
+          AND move_num = $2
+          AND NOT EXISTS (
+            SELECT
+            FROM games.t_moves later
+            WHERE later.id_game = $1
+              AND later.move_num > $2
+          )
+        RETURNING previous_fen
+        ",
+        payload.board_id,
+        request.move_num,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting last move {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_active
+        SET fen = $1
+        WHERE id = $2
+        ",
+        fen,
+        payload.board_id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error restoring board {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Queued moves answered a position that is gone
+    sqlx::query!(
+        "
+        DELETE FROM games.t_premoves
+        WHERE id_game = $1
+        ",
+        payload.board_id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error clearing premoves {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    hub.publish(payload.board_id, GameEvent::TakenBack { fen });
+
+    Ok(StatusCode::OK)
+}
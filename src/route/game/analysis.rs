@@ -0,0 +1,139 @@
+use crate::{authentication::LoggedUser, game};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Result,
+    Extension, Json,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Analysis of a finished game, or how far along it is
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<Json<Analysis>> {
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if !game::players(&mut conn, id)
+        .await?
+        .includes(user.username())
+    {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+
+    let job = sqlx::query!(
+        "
+        SELECT started_at IS NOT NULL as running, failed_at IS NOT NULL as failed
+        FROM games.t_analysis_jobs
+        WHERE id_game = $1
+        ",
+        id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting analysis job {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(job) = job {
+        let status = match (job.failed, job.running) {
+            (Some(true), _) => Status::Failed,
+            (_, Some(true)) => Status::Running,
+            _ => Status::Queued,
+        };
+        return Ok(Json(Analysis {
+            status,
+            moves: Vec::new(),
+            accuracy: None,
+        }));
+    }
+
+    let moves = sqlx::query_as!(
+        AnalysedMove,
+        "
+        SELECT ply, san, eval, best, loss, class
+        FROM games.t_analysis
+        WHERE id_game = $1
+        ORDER BY ply
+        ",
+        id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting analysis {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if moves.is_empty() {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    // Games without a move of one side leave its accuracy out
+    let accuracy = sqlx::query_as!(
+        Accuracy,
+        "
+        SELECT
+            AVG(accuracy) FILTER (WHERE white) as white,
+            AVG(accuracy) FILTER (WHERE NOT white) as black
+        FROM games.t_analysis
+        WHERE id_game = $1
+        ",
+        id,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting accuracy {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(Analysis {
+        status: Status::Done,
+        moves,
+        accuracy: Some(accuracy),
+    }))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Queued,
+    Running,
+    Failed,
+    Done,
+}
+
+#[derive(Serialize)]
+pub struct Analysis {
+    status: Status,
+    moves: Vec<AnalysedMove>,
+    accuracy: Option<Accuracy>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct AnalysedMove {
+    ply: i32,
+    san: String,
+    /// Centipawns from white's side after the move
+    eval: i32,
+    /// Engine's choice in the position before
+    best: Option<String>,
+    loss: i32,
+    class: String,
+}
+
+/// Mean accuracy in percent of each player's moves
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Accuracy {
+    white: Option<f64>,
+    black: Option<f64>,
+}
@@ -0,0 +1,55 @@
+use crate::{authentication::LoggedUser, game};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Result,
+    Extension,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Queue a finished game for analysis
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    info!("Queueing analysis");
+
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let players = game::players(&mut conn, id).await?;
+    if !players.includes(user.username()) {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+    if players.active {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    // Queued or running jobs are left alone, failed ones start over
+    sqlx::query!(
+        "
+        INSERT INTO games.t_analysis_jobs(id_game)
+        VALUES ($1)
+        ON CONFLICT (id_game) DO UPDATE
+        SET queued_at = now(),
+            started_at = NULL,
+            attempts = 0,
+            failed_at = NULL
+        WHERE t_analysis_jobs.failed_at IS NOT NULL
+        ",
+        id,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error queueing analysis {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,39 @@
+use super::ImportRequest;
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Turn down a game imported against the user, or withdraw one the user
+/// imported
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<ImportRequest>,
+) -> Result<StatusCode> {
+    info!("Declining import");
+
+    let affected = sqlx::query!(
+        "
+        DELETE FROM games.t_pending_imports
+        WHERE id = $1
+          AND (opponent = $2 OR importer = $2)
+        ",
+        payload.id,
+        user.username(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error declining import {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    Ok(StatusCode::OK)
+}
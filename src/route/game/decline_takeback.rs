@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use super::TakebackRequest;
+use crate::{
+    authentication::LoggedUser,
+    hub::{GameEvent, Hub},
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument(skip(hub))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<TakebackRequest>,
+) -> Result<StatusCode> {
+    info!("Declining takeback");
+
+    let affected = sqlx::query!(
+        "
+        DELETE FROM games.t_takebacks tb
+        USING games.t_active ac
+        WHERE ac.id = tb.id_game
+          AND tb.id_game = $1
+          AND tb.requester <> $2
+          AND (ac.player_w = $2 OR ac.player_b = $2)
+        ",
+        payload.board_id,
+        user.username(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error declining takeback {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    hub.publish(payload.board_id, GameEvent::TakebackDeclined);
+
+    Ok(StatusCode::OK)
+}
@@ -12,14 +12,18 @@ pub async fn handler(
         FGames,
         "
         SELECT
+            id,
             CASE
-                WHEN player_w = $1 THEN player_b
-                WHEN player_b = $1 THEN player_w
+                WHEN player_w = $1 AND NOT guest_w THEN player_b
+                ELSE player_w
             END as opponent,
-            moves as pgn
+            moves as pgn,
+            result,
+            eco,
+            opening
         FROM games.t_finished
-        WHERE player_w = $1
-           OR player_b = $1
+        WHERE (player_w = $1 AND NOT guest_w)
+           OR (player_b = $1 AND NOT guest_b)
         ORDER BY end_date DESC
         ",
         user.username(),
@@ -36,6 +40,10 @@ pub async fn handler(
 
 #[derive(serde::Serialize, sqlx::FromRow)]
 pub struct FGames {
+    id: i64,
     opponent: Option<String>,
     pgn: Option<String>,
+    result: Option<String>,
+    eco: Option<String>,
+    opening: Option<String>,
 }
@@ -0,0 +1,261 @@
+use crate::{
+    authentication::LoggedUser,
+    opening,
+    pgn::{self, Invalid},
+    position, social,
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Result},
+    Extension, Json,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+
+/// Import over-the-board games from a PGN file into the players' history.
+/// The user must have played every game. Games against another member are
+/// only added once that member accepts them. Nothing is imported unless every
+/// game is valid.
+#[tracing::instrument(skip(body))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    body: String,
+) -> Result<Json<Imported>> {
+    info!("Importing PGN");
+
+    let games = pgn::parse(&body);
+    if games.is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY.into());
+    }
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut imports = Vec::with_capacity(games.len());
+    let mut reports = Vec::new();
+    for (game, pgn) in games.iter().enumerate() {
+        match check(&mut trx, user.username(), pgn).await? {
+            Ok(import) => imports.push(import),
+            Err(error) => reports.push(Report { game, error }),
+        }
+    }
+
+    if !reports.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(reports))
+            .into_response()
+            .into());
+    }
+
+    let mut imported = Vec::with_capacity(imports.len());
+    let mut pending = Vec::new();
+    for import in imports {
+        match &import.opponent {
+            Some(opponent) => {
+                pending.push(hold(&mut trx, user.username(), opponent, &import).await?)
+            }
+            None => imported.push(store(&mut trx, &import).await?),
+        }
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!(
+        "Imported {} games, {} waiting for the opponent",
+        imported.len(),
+        pending.len()
+    );
+
+    Ok(Json(Imported { imported, pending }))
+}
+
+/// A game ready to be stored
+pub(super) struct Import {
+    pub(super) player_w: String,
+    pub(super) player_b: String,
+    /// Sides played by someone who is not a member
+    pub(super) guest_w: bool,
+    pub(super) guest_b: bool,
+    /// Member the user played, who has to confirm the game first
+    pub(super) opponent: Option<String>,
+    pub(super) start_pos: String,
+    pub(super) moves: String,
+    pub(super) result: String,
+    pub(super) date: Option<NaiveDateTime>,
+}
+
+/// Store `import` with the finished games, returning its id
+pub(super) async fn store(trx: &mut PgConnection, import: &Import) -> Result<i64, StatusCode> {
+    let opening = opening::classify(&import.start_pos, &import.moves);
+    // Ids are shared with games played here
+    let id = sqlx::query_scalar!(
+        "
+        INSERT INTO games.t_finished(
+            id,
+            player_w,
+            player_b,
+            guest_w,
+            guest_b,
+            start_pos,
+            moves,
+            result,
+            end_date,
+            imported,
+            eco,
+            opening
+        )
+        VALUES (nextval('games.t_active_id_seq'), $1, $2, $3, $4, $5, $6, $7, COALESCE($8, localtimestamp), true, $9, $10)
+        RETURNING id
+        ",
+        import.player_w,
+        import.player_b,
+        import.guest_w,
+        import.guest_b,
+        import.start_pos,
+        import.moves,
+        import.result,
+        import.date,
+        opening.map(|opening| opening.eco),
+        opening.map(|opening| opening.name),
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error inserting imported game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    position::index(trx, id, &import.start_pos, &import.moves).await?;
+
+    Ok(id)
+}
+
+/// Keep `import` until `opponent` confirms it, returning the pending id
+async fn hold(
+    trx: &mut PgConnection,
+    importer: &str,
+    opponent: &str,
+    import: &Import,
+) -> Result<i64, StatusCode> {
+    sqlx::query_scalar!(
+        "
+        INSERT INTO games.t_pending_imports(importer, opponent, player_w, player_b, start_pos, moves, result, end_date)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        ",
+        importer,
+        opponent,
+        import.player_w,
+        import.player_b,
+        import.start_pos,
+        import.moves,
+        import.result,
+        import.date,
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error inserting pending import {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+async fn check(
+    conn: &mut PgConnection,
+    username: &str,
+    pgn: &pgn::Game,
+) -> Result<Result<Import, Invalid>, StatusCode> {
+    let (Some(white), Some(black)) = (player(pgn, "White"), player(pgn, "Black")) else {
+        let tag = if player(pgn, "White").is_none() {
+            "White"
+        } else {
+            "Black"
+        };
+        return Ok(Err(Invalid::MissingTag { tag }));
+    };
+
+    // Names of members are stored as they registered them
+    let member_w = member(conn, white).await?;
+    let member_b = member(conn, black).await?;
+    let opponent = match (member_w.as_deref(), member_b.as_deref()) {
+        (Some(player), opponent) | (opponent, Some(player)) if player == username => opponent,
+        _ => return Ok(Err(Invalid::NotAPlayer)),
+    };
+    // Games against another member wait for them to confirm
+    let opponent = opponent
+        .filter(|opponent| *opponent != username)
+        .map(str::to_string);
+    if let Some(opponent) = &opponent {
+        if social::blocked_between(conn, username, opponent).await? {
+            return Ok(Err(Invalid::Blocked {
+                username: opponent.clone(),
+            }));
+        }
+    }
+    let (guest_w, guest_b) = (member_w.is_none(), member_b.is_none());
+    let player_w = member_w.unwrap_or_else(|| white.to_string());
+    let player_b = member_b.unwrap_or_else(|| black.to_string());
+
+    let Some(result) = pgn.result() else {
+        return Ok(Err(Invalid::NoResult));
+    };
+
+    Ok(pgn.replay().map(|replayed| Import {
+        player_w,
+        player_b,
+        guest_w,
+        guest_b,
+        opponent,
+        start_pos: replayed.start.to_string(),
+        moves: replayed.moves.join(" "),
+        result: result.to_string(),
+        date: pgn.date().and_then(|date| date.and_hms_opt(0, 0, 0)),
+    }))
+}
+
+/// Player tag, unless left unknown
+fn player<'a>(pgn: &'a pgn::Game, tag: &str) -> Option<&'a str> {
+    pgn.tag(tag)
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "?")
+}
+
+async fn member(conn: &mut PgConnection, name: &str) -> Result<Option<String>, StatusCode> {
+    sqlx::query_scalar!(
+        "
+        SELECT username
+        FROM users.basic_info
+        WHERE lower(username) = lower($1)
+        ",
+        name,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error looking up player {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Why the game at index `game` of the file was rejected
+#[derive(Serialize)]
+pub struct Report {
+    game: usize,
+    #[serde(flatten)]
+    error: Invalid,
+}
+
+#[derive(Serialize)]
+pub struct Imported {
+    /// Ids of the games added
+    imported: Vec<i64>,
+    /// Ids of the imports waiting for the opponent
+    pending: Vec<i64>,
+}
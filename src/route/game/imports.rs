@@ -0,0 +1,51 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Json, Result},
+    Extension,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Games other members imported against the user, waiting for them to
+/// accept or decline
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<Json<Vec<PendingImport>>, StatusCode> {
+    info!("Checking imports");
+
+    let res = sqlx::query_as!(
+        PendingImport,
+        "
+        SELECT id, importer, player_w, player_b, moves, result, end_date
+        FROM games.t_pending_imports
+        WHERE opponent = $1
+        ORDER BY created_at DESC
+        ",
+        user.username(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error checking imports {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(res))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct PendingImport {
+    id: i64,
+    importer: String,
+    player_w: String,
+    player_b: String,
+    moves: String,
+    result: String,
+    end_date: Option<NaiveDateTime>,
+}
@@ -1,26 +1,69 @@
-use crate::authentication::LoggedUser;
+use crate::{
+    authentication::LoggedUser,
+    bot,
+    game::{self, Event},
+    hub::{Hub, UserEvent},
+    social, uci,
+};
 use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
 use serde::Deserialize;
 use sqlx::{error::ErrorKind, PgPool};
+use std::sync::Arc;
 use tracing::{error, info};
 
-#[tracing::instrument]
+#[tracing::instrument(skip(hub, engines))]
 pub async fn handler(
     State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    State(engines): State<Option<Arc<uci::Pool>>>,
     Extension(user): Extension<LoggedUser>,
     Json(payload): Json<Invitation>,
 ) -> Result<StatusCode> {
     info!("Inviting");
 
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if social::blocked_between(&mut conn, user.username(), &payload.invited).await?
+        || !social::accepts_invites_from(&mut conn, user.username(), &payload.invited).await?
+    {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    // Bots take every invite, the game starts right away
+    if let Some(engine) = bot::engine(&mut conn, &payload.invited).await? {
+        // Nothing could answer for a bot whose engine is not configured
+        if engine == bot::Engine::Uci && engines.is_none() {
+            return Err(StatusCode::SERVICE_UNAVAILABLE.into());
+        }
+
+        let (_, start) = game::create(
+            &mut conn,
+            user.username(),
+            &payload.invited,
+            payload.public,
+            false,
+            Event::Casual,
+        )
+        .await?;
+        hub.publish_user(user.username(), start);
+
+        return Ok(StatusCode::OK);
+    }
+
     sqlx::query!(
         "
-        INSERT INTO games.v_pending_invites (inviter, invited)
-        VALUES ($1, $2)
+        INSERT INTO games.v_pending_invites (inviter, invited, public, takebacks)
+        VALUES ($1, $2, $3, $4)
         ",
         user.username(),
         payload.invited,
+        payload.public,
+        payload.takebacks,
     )
-    .execute(&postgres)
+    .execute(&mut *conn)
     .await
     .map_err(|err| {
         if err
@@ -34,10 +77,29 @@ pub async fn handler(
         }
     })?;
 
+    hub.publish_user(
+        &payload.invited,
+        UserEvent::Challenge {
+            inviter: user.username().clone(),
+            public: payload.public,
+            takebacks: payload.takebacks,
+        },
+    );
+
     Ok(StatusCode::OK)
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Invitation {
     invited: String,
+    /// Whether anyone can watch the game
+    #[serde(default)]
+    public: bool,
+    /// Whether players can ask to undo their last move. Bots never allow it.
+    #[serde(default = "default_takebacks")]
+    takebacks: bool,
+}
+
+fn default_takebacks() -> bool {
+    true
 }
@@ -22,6 +22,14 @@ pub async fn handler(
         SELECT inviter
         FROM games.v_pending_invites
         WHERE invited = $1
+          AND NOT EXISTS (
+            SELECT
+            FROM users.blocks bl
+                JOIN users.basic_info u1 ON u1.id = bl.blocker_id
+                JOIN users.basic_info u2 ON u2.id = bl.blocked_id
+            WHERE (u1.username = inviter AND u2.username = invited)
+               OR (u1.username = invited AND u2.username = inviter)
+          )
         ORDER BY created_at DESC
         ",
         user.username(),
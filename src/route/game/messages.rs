@@ -0,0 +1,62 @@
+use crate::{authentication::LoggedUser, game};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Result,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Query(payload): Query<GetMessages>,
+) -> Result<Json<Vec<ChatMessage>>> {
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Chat stays readable by the players once the game is over
+    if !game::players(&mut conn, payload.id)
+        .await?
+        .includes(user.username())
+    {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+
+    let messages = sqlx::query_as!(
+        ChatMessage,
+        r#"
+        SELECT author, body, sent_at::timestamptz as "sent_at!"
+        FROM games.t_chat
+        WHERE id_game = $1
+        ORDER BY id
+        "#,
+        payload.id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting messages {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(messages))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetMessages {
+    id: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct ChatMessage {
+    author: String,
+    body: String,
+    sent_at: DateTime<Utc>,
+}
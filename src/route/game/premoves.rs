@@ -0,0 +1,43 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Result,
+    Extension, Json,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Conditional moves the user has queued in a game
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Query(payload): Query<GetPremoves>,
+) -> Result<Json<Vec<Vec<String>>>> {
+    let lines = sqlx::query_scalar!(
+        "
+        SELECT moves
+        FROM games.t_premoves
+        WHERE id_game = $1
+          AND player = $2
+        ORDER BY id
+        ",
+        payload.id,
+        user.username(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting premoves {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(lines))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetPremoves {
+    id: i64,
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::{
+    authentication::LoggedUser,
+    config::Config,
+    game,
+    hub::{GameEvent, Hub},
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument(skip(hub))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Message>,
+) -> Result<StatusCode> {
+    info!("Sending message");
+
+    let body = payload.body.trim();
+    if body.is_empty() {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+    if body.chars().count() > config.chat_max_len {
+        return Err(StatusCode::PAYLOAD_TOO_LARGE.into());
+    }
+
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Only the players of an ongoing game can talk
+    let players = game::players(&mut conn, payload.board_id).await?;
+    if !players.includes(user.username()) {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+    if !players.active {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    let sent_at = sqlx::query_scalar!(
+        r#"
+        INSERT INTO games.t_chat(id_game, author, body)
+        VALUES ($1, $2, $3)
+        RETURNING sent_at::timestamptz as "sent_at!"
+        "#,
+        payload.board_id,
+        user.username(),
+        body,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error inserting message {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    hub.publish(
+        payload.board_id,
+        GameEvent::Chat {
+            author: user.username().clone(),
+            body: body.to_string(),
+            sent_at,
+        },
+    );
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Message {
+    board_id: i64,
+    body: String,
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use crate::{
+    authentication::LoggedUser,
+    game::premove::{self, InvalidLine},
+};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Result},
+    Extension, Json,
+};
+use chess::Board;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Replace the conditional moves queued in a game
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Premoves>,
+) -> Result<StatusCode> {
+    info!("Queueing premoves");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let cgame = sqlx::query!(
+        "
+        SELECT fen, player_w, player_b
+        FROM games.t_active
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        payload.board_id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let board = Board::from_str(&cgame.fen).map_err(|err| {
+        error!("Error interpreting fen from database {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Lines answer the opponent, so it has to be the opponent's turn
+    let (me, opponent) = match board.side_to_move() {
+        chess::Color::White => (&cgame.player_b, &cgame.player_w),
+        chess::Color::Black => (&cgame.player_w, &cgame.player_b),
+    };
+    if me != user.username() {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+    if me == opponent {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    premove::validate(&board, &payload.lines).map_err(invalid)?;
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_premoves
+        WHERE id_game = $1
+          AND player = $2
+        ",
+        payload.board_id,
+        user.username(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error clearing premoves {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for moves in &payload.lines {
+        sqlx::query!(
+            "
+            INSERT INTO games.t_premoves(id_game, player, moves)
+            VALUES ($1, $2, $3)
+            ",
+            payload.board_id,
+            user.username(),
+            moves,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error storing premoves {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+fn invalid(line: InvalidLine) -> axum::response::ErrorResponse {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(line))
+        .into_response()
+        .into()
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Premoves {
+    board_id: i64,
+    /// Alternating moves in SAN, the opponent's first. An empty list
+    /// clears the queue.
+    lines: Vec<Vec<String>>,
+}
@@ -0,0 +1,65 @@
+use std::{convert::Infallible, sync::Arc};
+
+use crate::{authentication::LoggedUser, game, hub::Hub, presence::Presence};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        Result, Sse,
+    },
+    Extension,
+};
+use futures_util::Stream;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tracing::error;
+
+/// Server-sent events of a game: moves, its end and chat messages
+#[tracing::instrument(skip(hub, presence))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    State(presence): State<Arc<Presence>>,
+    Extension(user): Extension<LoggedUser>,
+    Query(payload): Query<Subscribe>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error acquiring connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Subscribe before checking the game is on, so a game finishing in
+    // between does not leave a channel nobody will close
+    let events = hub.subscribe(payload.id);
+
+    let players = game::players(&mut conn, payload.id).await?;
+    if !players.includes(user.username()) {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+    if !players.active {
+        hub.close(payload.id);
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    // The user stays online for as long as the stream is open
+    let connection = presence.connect(user.id());
+
+    let events = BroadcastStream::new(events).filter_map(move |event| {
+        // Owned by the stream, dropped when the client goes away
+        let _ = &connection;
+        // Lagging subscribers just miss the events that were dropped
+        let event = event.ok()?;
+        Some(Ok(Event::default()
+            .json_data(event)
+            .expect("events serialize")))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Subscribe {
+    id: i64,
+}
@@ -0,0 +1,114 @@
+use std::{str::FromStr, sync::Arc};
+
+use super::TakebackRequest;
+use crate::{
+    authentication::LoggedUser,
+    hub::{GameEvent, Hub},
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use chess::Board;
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+/// Ask the opponent to undo our last move
+#[tracing::instrument(skip(hub))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<TakebackRequest>,
+) -> Result<StatusCode> {
+    info!("Requesting takeback");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let cgame = sqlx::query!(
+        "
+        SELECT
+            ac.fen,
+            ac.player_w,
+            ac.player_b,
+            ac.takebacks,
+            (
+                SELECT MAX(mo.move_num)
+                FROM games.t_moves mo
+                WHERE mo.id_game = ac.id
+            ) as last_move
+        FROM games.t_active ac
+        WHERE ac.id = $1
+        FOR UPDATE
+        ",
+        payload.board_id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if &cgame.player_w != user.username() && &cgame.player_b != user.username() {
+        return Err(StatusCode::UNAUTHORIZED.into());
+    }
+
+    if !cgame.takebacks {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let last_move = cgame.last_move.ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    let board = Board::from_str(&cgame.fen).map_err(|err| {
+        error!("Error interpreting fen from database {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Only the player who just moved can take it back
+    let player_to_move = match board.side_to_move() {
+        chess::Color::White => &cgame.player_w,
+        chess::Color::Black => &cgame.player_b,
+    };
+    if player_to_move == user.username() && cgame.player_w != cgame.player_b {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO games.t_takebacks(id_game, requester, move_num)
+        VALUES ($1, $2, $3)
+        ",
+        payload.board_id,
+        user.username(),
+        last_move,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        if err
+            .as_database_error()
+            .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
+        {
+            StatusCode::NOT_ACCEPTABLE
+        } else {
+            error!("Error requesting takeback {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    hub.publish(
+        payload.board_id,
+        GameEvent::TakebackRequested {
+            by: user.username().clone(),
+        },
+    );
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+const MAX_PER_PAGE: i64 = 100;
+
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Query(params): Query<Params>,
+) -> Result<Json<Leaderboard>, StatusCode> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(50).clamp(1, MAX_PER_PAGE);
+
+    // Only players with at least one game inside the window are ranked
+    let entries = sqlx::query_as!(
+        Entry,
+        r#"
+        SELECT
+            RANK() OVER (ORDER BY CASE WHEN $2 THEN SUM(d.wins) ELSE u.rating END DESC) as "rank!",
+            u.username,
+            u.rating,
+            SUM(d.wins)::int as "wins!",
+            SUM(d.draws)::int as "draws!",
+            SUM(d.losses)::int as "losses!"
+        FROM users.basic_info u
+            JOIN games.t_daily_results d ON d.username = u.username
+        WHERE $1::int IS NULL
+           OR d.day > current_date - $1::int
+        GROUP BY u.username, u.rating
+        ORDER BY 1, u.username
+        LIMIT $3
+        OFFSET $4
+        "#,
+        params.window.days(),
+        params.by == Ranking::Wins,
+        per_page,
+        (page - 1).saturating_mul(per_page),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting leaderboard {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(Leaderboard {
+        page,
+        per_page,
+        entries,
+    }))
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Ranking {
+    #[default]
+    Rating,
+    Wins,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub(crate) enum Window {
+    #[default]
+    #[serde(rename = "all")]
+    AllTime,
+    #[serde(rename = "30d")]
+    Last30Days,
+}
+
+impl Window {
+    fn days(&self) -> Option<i32> {
+        match self {
+            Window::AllTime => None,
+            Window::Last30Days => Some(30),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Params {
+    #[serde(default)]
+    by: Ranking,
+    #[serde(default)]
+    window: Window,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct Entry {
+    rank: i64,
+    username: String,
+    rating: i32,
+    wins: i32,
+    draws: i32,
+    losses: i32,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Leaderboard {
+    page: i64,
+    per_page: i64,
+    entries: Vec<Entry>,
+}
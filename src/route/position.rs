@@ -0,0 +1,21 @@
+//! Finished games looked up by the positions they went through
+
+mod explorer;
+mod search;
+
+pub(crate) use explorer::handler as explorer;
+pub(crate) use search::handler as search;
+
+use std::str::FromStr;
+
+use axum::http::StatusCode;
+use chess::Board;
+
+use crate::position;
+
+/// Key of the position in `fen`, whatever its move counters
+fn key(fen: &str) -> Result<String, StatusCode> {
+    Board::from_str(fen)
+        .map(|board| position::key(&board))
+        .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)
+}
@@ -0,0 +1,82 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+/// Moves played from a position in finished games, most played first, with
+/// how those games ended
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Query(params): Query<Params>,
+) -> Result<Json<Explorer>, StatusCode> {
+    let key = super::key(&params.fen)?;
+
+    // A game repeating the position counts once for each move tried from it
+    let moves = sqlx::query_as!(
+        Next,
+        r#"
+        SELECT
+            po.next_san as "san!",
+            COUNT(DISTINCT fi.id) as "games!",
+            COUNT(DISTINCT fi.id) FILTER (WHERE fi.result = '1-0') as "white_wins!",
+            COUNT(DISTINCT fi.id) FILTER (WHERE fi.result = '1/2-1/2') as "draws!",
+            COUNT(DISTINCT fi.id) FILTER (WHERE fi.result = '0-1') as "black_wins!"
+        FROM games.t_positions po
+            JOIN games.t_finished fi ON fi.id = po.id_game
+        WHERE po.position_key = $1
+          AND po.next_san IS NOT NULL
+        GROUP BY po.next_san
+        ORDER BY 2 DESC, 1
+        "#,
+        key,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error exploring position {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let games = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(DISTINCT id_game) as "games!"
+        FROM games.t_positions
+        WHERE position_key = $1
+        "#,
+        key,
+    )
+    .fetch_one(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error counting games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(Explorer { games, moves }))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Params {
+    fen: String,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct Next {
+    san: String,
+    games: i64,
+    white_wins: i64,
+    draws: i64,
+    black_wins: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Explorer {
+    /// Games that reached the position, including those ending there
+    games: i64,
+    moves: Vec<Next>,
+}
@@ -0,0 +1,80 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+const MAX_PER_PAGE: i64 = 100;
+
+/// Finished games that reached a position, newest first, with the first
+/// ply it was reached at
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Query(params): Query<Params>,
+) -> Result<Json<Found>, StatusCode> {
+    let key = super::key(&params.fen)?;
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params.per_page.unwrap_or(50).clamp(1, MAX_PER_PAGE);
+
+    let games = sqlx::query_as!(
+        Game,
+        r#"
+        SELECT fi.id, po.ply as "ply!", fi.player_w, fi.player_b, fi.result, fi.end_date
+        FROM (
+            SELECT DISTINCT ON (id_game) id_game, ply
+            FROM games.t_positions
+            WHERE position_key = $1
+            ORDER BY id_game, ply
+        ) po
+            JOIN games.t_finished fi ON fi.id = po.id_game
+        ORDER BY fi.end_date DESC, fi.id DESC
+        LIMIT $2
+        OFFSET $3
+        "#,
+        key,
+        per_page,
+        (page - 1).saturating_mul(per_page),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error searching position {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(Found {
+        page,
+        per_page,
+        games,
+    }))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Params {
+    fen: String,
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct Game {
+    id: i64,
+    /// Half moves played before the position, 0 for the start
+    ply: i32,
+    player_w: String,
+    player_b: String,
+    result: Option<String>,
+    end_date: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Found {
+    page: i64,
+    per_page: i64,
+    games: Vec<Game>,
+}
@@ -0,0 +1,7 @@
+//! Training on puzzles, rated separately from games
+
+mod next;
+mod play;
+
+pub(crate) use next::handler as next;
+pub(crate) use play::handler as play;
@@ -0,0 +1,169 @@
+use crate::{authentication::LoggedUser, puzzle::Puzzle};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Puzzles this far from the user's rating either way are picked from
+const CANDIDATES: i64 = 10;
+
+/// The puzzle being solved, or a new one close to the user's puzzle rating
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<Json<Next>, StatusCode> {
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Locking the user keeps concurrent requests from starting two puzzles
+    let puzzle_rating = sqlx::query_scalar!(
+        "
+        SELECT puzzle_rating
+        FROM users.basic_info
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        user.id(),
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting puzzle rating {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = sqlx::query_as!(
+        Current,
+        "
+        SELECT p.id, p.fen, p.moves, p.rating, p.themes, a.ply
+        FROM games.t_puzzle_attempts a
+            JOIN games.t_puzzles p ON p.id = a.puzzle_id
+        WHERE a.user_id = $1
+          AND a.solved IS NULL
+        ",
+        user.id(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting current puzzle {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = match current {
+        Some(current) => current,
+        None => {
+            let current = sqlx::query_as!(
+                Current,
+                r#"
+                SELECT id as "id!", fen as "fen!", moves as "moves!", rating as "rating!",
+                    themes as "themes!", 1 as "ply!"
+                FROM (
+                    (
+                        SELECT *
+                        FROM games.t_puzzles p
+                        WHERE rating >= $2
+                          AND NOT EXISTS (
+                            SELECT
+                            FROM games.t_puzzle_attempts a
+                            WHERE a.user_id = $1
+                              AND a.puzzle_id = p.id
+                          )
+                        ORDER BY rating
+                        LIMIT $3
+                    )
+                    UNION ALL
+                    (
+                        SELECT *
+                        FROM games.t_puzzles p
+                        WHERE rating < $2
+                          AND NOT EXISTS (
+                            SELECT
+                            FROM games.t_puzzle_attempts a
+                            WHERE a.user_id = $1
+                              AND a.puzzle_id = p.id
+                          )
+                        ORDER BY rating DESC
+                        LIMIT $3
+                    )
+                ) p
+                ORDER BY random()
+                LIMIT 1
+                "#,
+                user.id(),
+                puzzle_rating,
+                CANDIDATES,
+            )
+            .fetch_optional(&mut *trx)
+            .await
+            .map_err(|err| {
+                error!("Error picking puzzle {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+            sqlx::query!(
+                "
+                INSERT INTO games.t_puzzle_attempts(user_id, puzzle_id)
+                VALUES ($1, $2)
+                ",
+                user.id(),
+                current.id,
+            )
+            .execute(&mut *trx)
+            .await
+            .map_err(|err| {
+                error!("Error starting puzzle {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+            current
+        }
+    };
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let puzzle = Puzzle::new(&current.fen, &current.moves).ok_or_else(|| {
+        error!("Puzzle {} has an invalid solution", current.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let ply = current.ply as usize;
+
+    Ok(Json(Next {
+        fen: puzzle.board(ply).to_string(),
+        last_move: puzzle.last_move(ply),
+        id: current.id,
+        rating: current.rating,
+        themes: current.themes,
+        puzzle_rating,
+    }))
+}
+
+#[derive(sqlx::FromRow)]
+struct Current {
+    id: String,
+    fen: String,
+    moves: String,
+    rating: i32,
+    themes: Vec<String>,
+    ply: i32,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Next {
+    id: String,
+    /// Position to play a move in
+    fen: String,
+    /// Opponent's move leading to `fen`
+    last_move: String,
+    rating: i32,
+    themes: Vec<String>,
+    /// Rating of the user
+    puzzle_rating: i32,
+}
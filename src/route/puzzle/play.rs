@@ -0,0 +1,195 @@
+use crate::{
+    authentication::LoggedUser,
+    notation,
+    puzzle::{Puzzle, Step},
+    rating,
+};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chess::ChessMove;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::str::FromStr;
+use tracing::error;
+
+/// Check a move of the puzzle being solved. A wrong move or the last one
+/// ends the attempt and updates the user's puzzle rating.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<PuzzleMove>,
+) -> Result<Json<Checked>, StatusCode> {
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let attempt = sqlx::query!(
+        "
+        SELECT p.fen, p.moves, p.rating, a.ply
+        FROM games.t_puzzle_attempts a
+            JOIN games.t_puzzles p ON p.id = a.puzzle_id
+        WHERE a.user_id = $1
+          AND a.puzzle_id = $2
+          AND a.solved IS NULL
+        FOR UPDATE OF a
+        ",
+        user.id(),
+        payload.id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting puzzle attempt {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let puzzle = Puzzle::new(&attempt.fen, &attempt.moves).ok_or_else(|| {
+        error!("Puzzle {} has an invalid solution", payload.id);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let ply = attempt.ply as usize;
+
+    // Illegal moves are refused like in games, they are no wrong answer
+    let board = puzzle.board(ply);
+    let cmove = notation::parse_san(&board, &payload.san)
+        .ok()
+        .or_else(|| {
+            ChessMove::from_str(payload.san.trim())
+                .ok()
+                .filter(|cmove| board.legal(*cmove))
+        })
+        .ok_or(StatusCode::NOT_ACCEPTABLE)?;
+
+    let checked = match puzzle.play(ply, cmove) {
+        Step::Correct { reply, ply } => {
+            sqlx::query!(
+                "
+                UPDATE games.t_puzzle_attempts
+                SET ply = $1
+                WHERE user_id = $2
+                  AND puzzle_id = $3
+                ",
+                ply as i32,
+                user.id(),
+                payload.id,
+            )
+            .execute(&mut *trx)
+            .await
+            .map_err(|err| {
+                error!("Error updating puzzle attempt {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+            Checked::Correct {
+                reply,
+                fen: puzzle.board(ply).to_string(),
+            }
+        }
+        Step::Wrong => Checked::Wrong {
+            solution: puzzle.line(ply),
+            puzzle_rating: finish(&mut trx, user.id(), &payload.id, attempt.rating, false).await?,
+        },
+        Step::Solved => Checked::Solved {
+            puzzle_rating: finish(&mut trx, user.id(), &payload.id, attempt.rating, true).await?,
+        },
+    };
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(checked))
+}
+
+/// End the attempt and rate the user against the puzzle, returning the
+/// user's new rating
+async fn finish(
+    trx: &mut sqlx::PgConnection,
+    user_id: i64,
+    puzzle_id: &str,
+    puzzle_rating: i32,
+    solved: bool,
+) -> Result<i32, StatusCode> {
+    sqlx::query!(
+        "
+        UPDATE games.t_puzzle_attempts
+        SET solved = $1
+        WHERE user_id = $2
+          AND puzzle_id = $3
+        ",
+        solved,
+        user_id,
+        puzzle_id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error finishing puzzle attempt {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = sqlx::query_scalar!(
+        "
+        SELECT puzzle_rating
+        FROM users.basic_info
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        user_id,
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting puzzle rating {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Puzzle ratings come from the imported file and stay as they are
+    let (new_rating, _) = rating::elo(current, puzzle_rating, if solved { 1.0 } else { 0.0 });
+
+    sqlx::query!(
+        "
+        UPDATE users.basic_info
+        SET puzzle_rating = $1
+        WHERE id = $2
+        ",
+        new_rating,
+        user_id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error updating puzzle rating {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(new_rating)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct PuzzleMove {
+    id: String,
+    /// SAN or UCI
+    san: String,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub(crate) enum Checked {
+    /// The opponent answered with `reply`, leaving the board at `fen`
+    Correct {
+        reply: String,
+        fen: String,
+    },
+    Wrong {
+        /// What should have been played from there
+        solution: Vec<String>,
+        puzzle_rating: i32,
+    },
+    Solved {
+        puzzle_rating: i32,
+    },
+}
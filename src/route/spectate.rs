@@ -0,0 +1,16 @@
+//! Read-only access to public games, for anyone besides their players
+
+mod events;
+mod live;
+mod watch;
+
+pub use events::handler as events;
+pub use live::handler as live;
+pub use watch::handler as watch;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Watch {
+    id: i64,
+}
@@ -0,0 +1,60 @@
+use std::{convert::Infallible, sync::Arc};
+
+use super::Watch;
+use crate::hub::{GameEvent, Hub};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        Result, Sse,
+    },
+};
+use futures_util::Stream;
+use sqlx::PgPool;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tracing::error;
+
+/// Server-sent events of a public game, without the players' chat
+#[tracing::instrument(skip(hub))]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Query(payload): Query<Watch>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    // Subscribe first, like the players do
+    let events = hub.subscribe(payload.id);
+
+    let public = sqlx::query_scalar!(
+        "
+        SELECT public
+        FROM games.t_active
+        WHERE id = $1
+        ",
+        payload.id,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting game {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    match public {
+        Some(true) => {}
+        Some(false) => return Err(StatusCode::NOT_FOUND.into()),
+        // Over, or never existed
+        None => {
+            hub.close(payload.id);
+            return Err(StatusCode::NOT_FOUND.into());
+        }
+    }
+
+    let events = BroadcastStream::new(events).filter_map(|event| match event.ok()? {
+        GameEvent::Chat { .. } => None,
+        event => Some(Ok(Event::default()
+            .json_data(event)
+            .expect("events serialize"))),
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
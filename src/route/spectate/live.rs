@@ -0,0 +1,59 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Result,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+const MAX_PER_PAGE: i64 = 100;
+
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Query(params): Query<Params>,
+) -> Result<Json<Vec<LiveGame>>> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(MAX_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+
+    let games = sqlx::query_as!(
+        LiveGame,
+        "
+        SELECT id, player_w, player_b, fen
+        FROM games.t_active
+        WHERE public
+        ORDER BY id DESC
+        LIMIT $1
+        OFFSET $2
+        ",
+        per_page,
+        (page - 1).saturating_mul(per_page),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting live games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(games))
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Params {
+    page: Option<i64>,
+    per_page: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct LiveGame {
+    id: i64,
+    player_w: String,
+    player_b: String,
+    fen: String,
+}
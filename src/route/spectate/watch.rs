@@ -0,0 +1,43 @@
+use super::Watch;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Result,
+    Json,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+#[tracing::instrument]
+pub async fn handler(
+    State(postgres): State<PgPool>,
+    Query(payload): Query<Watch>,
+) -> Result<Json<Board>> {
+    let board = sqlx::query_as!(
+        Board,
+        "
+        SELECT player_w, player_b, fen
+        FROM games.t_active
+        WHERE id = $1
+          AND public
+        ",
+        payload.id,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting fen of a board {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(board))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Board {
+    player_w: String,
+    player_b: String,
+    fen: String,
+}
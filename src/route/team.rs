@@ -0,0 +1,48 @@
+//! Teams, their members and the matches they play against each other
+
+mod accept_challenge;
+mod challenge;
+mod create;
+mod decline_challenge;
+mod get;
+mod get_match;
+mod invite;
+mod join;
+mod leave;
+mod requests;
+
+pub(crate) use accept_challenge::handler as accept_challenge;
+pub(crate) use challenge::handler as challenge;
+pub(crate) use create::handler as create;
+pub(crate) use decline_challenge::handler as decline_challenge;
+pub(crate) use get::handler as get;
+pub(crate) use get_match::handler as get_match;
+pub(crate) use invite::handler as invite;
+pub(crate) use join::handler as join;
+pub(crate) use leave::handler as leave;
+pub(crate) use requests::handler as requests;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Member {
+    username: String,
+}
+
+/// Players of a team by board
+#[derive(Deserialize, Debug)]
+pub(crate) struct Lineup {
+    players: Vec<String>,
+}
+
+/// Where a match stands, from whether it was accepted and how many of its
+/// boards are over
+fn status(accepted: bool, finished: usize, boards: usize) -> &'static str {
+    if !accepted {
+        "proposed"
+    } else if finished < boards {
+        "playing"
+    } else {
+        "finished"
+    }
+}
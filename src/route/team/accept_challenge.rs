@@ -0,0 +1,133 @@
+use super::Lineup;
+use crate::{
+    authentication::LoggedUser,
+    game::{self, Event},
+    hub::Hub,
+    social, team, tournament,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Accept a challenge to the team we lead with our players by board,
+/// starting a game on every board
+#[tracing::instrument(skip(hub))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+    Json(payload): Json<Lineup>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Accepting match {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let challenge = sqlx::query!(
+        r#"
+        SELECT challenger as "challenger!", challenged as "challenged!", boards, lineup
+        FROM games.t_team_matches
+        WHERE id = $1
+          AND accepted_at IS NULL
+        FOR UPDATE
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting match {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    team::check_leader(&mut trx, challenge.challenged, user.id()).await?;
+    if payload.players.len() != challenge.boards as usize
+        || payload
+            .players
+            .iter()
+            .any(|player| challenge.lineup.contains(player))
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    team::check_lineup(&mut trx, challenge.challenged, &payload.players).await?;
+
+    // Players of the challenger may have left since
+    team::check_lineup(&mut trx, challenge.challenger, &challenge.lineup)
+        .await
+        .map_err(|err| match err {
+            StatusCode::UNPROCESSABLE_ENTITY => StatusCode::NOT_ACCEPTABLE,
+            err => err,
+        })?;
+
+    // Players who blocked each other are not made to play
+    for (ours, theirs) in payload.players.iter().zip(&challenge.lineup) {
+        if social::blocked_between(&mut trx, ours, theirs).await? {
+            return Err(StatusCode::NOT_ACCEPTABLE);
+        }
+    }
+
+    let mut starts = Vec::with_capacity(payload.players.len());
+    for (index, (ours, theirs)) in payload.players.iter().zip(&challenge.lineup).enumerate() {
+        let board = index + 1;
+        let challenger_white = team::challenger_white(board);
+        let (white, black) = if challenger_white {
+            (theirs, ours)
+        } else {
+            (ours, theirs)
+        };
+
+        let (id_game, start) =
+            game::create(&mut trx, white, black, true, false, Event::Casual).await?;
+        starts.push(start);
+
+        sqlx::query!(
+            "
+            INSERT INTO games.t_match_boards(match_id, board, id_game, challenger_white)
+            VALUES ($1, $2, $3, $4)
+            ",
+            id,
+            board as i32,
+            id_game,
+            challenger_white,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error inserting match board {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    sqlx::query!(
+        "
+        UPDATE games.t_team_matches
+        SET accepted_at = now()
+        WHERE id = $1
+        ",
+        id,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error accepting match {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tournament::announce(&hub, starts);
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,66 @@
+use crate::{authentication::LoggedUser, team};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+/// Challenge another team from the team we lead, with our players by board.
+/// Returns the id of the match.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+    Json(payload): Json<Challenge>,
+) -> Result<Json<i64>, StatusCode> {
+    info!("Challenging team {}", payload.team);
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    team::check_leader(&mut trx, id, user.id()).await?;
+    team::check_lineup(&mut trx, id, &payload.players).await?;
+
+    let match_id = sqlx::query_scalar!(
+        "
+        INSERT INTO games.t_team_matches(challenger, challenged, boards, lineup)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        ",
+        id,
+        payload.team,
+        payload.players.len() as i32,
+        &payload.players,
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| match err.as_database_error().map(|err| err.kind()) {
+        Some(ErrorKind::ForeignKeyViolation) => StatusCode::NOT_FOUND,
+        Some(ErrorKind::CheckViolation) => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => {
+            error!("Error creating match {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(match_id))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Challenge {
+    /// Team challenged
+    team: i64,
+    /// Our players, one per board
+    players: Vec<String>,
+}
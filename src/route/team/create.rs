@@ -0,0 +1,85 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::{error::ErrorKind, PgPool};
+use std::ops::RangeInclusive;
+use tracing::{error, info};
+
+const NAME_LENGTH: RangeInclusive<usize> = 1..=50;
+const DESCRIPTION_LENGTH: usize = 1000;
+
+/// Found a team led by the user, returning its id
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<NewTeam>,
+) -> Result<Json<i64>, StatusCode> {
+    info!("Creating team");
+
+    let name = payload.name.trim();
+    let description = payload.description.as_deref().unwrap_or("").trim();
+    if !NAME_LENGTH.contains(&name.chars().count())
+        || description.chars().count() > DESCRIPTION_LENGTH
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Names are unique whatever their case
+    let id = sqlx::query_scalar!(
+        "
+        INSERT INTO users.teams(name, description)
+        VALUES ($1, $2)
+        RETURNING id
+        ",
+        name,
+        description,
+    )
+    .fetch_one(&mut *trx)
+    .await
+    .map_err(|err| {
+        if err
+            .as_database_error()
+            .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
+        {
+            StatusCode::NOT_ACCEPTABLE
+        } else {
+            error!("Error creating team {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    sqlx::query!(
+        "
+        INSERT INTO users.team_members(team_id, user_id, leader, invited, accepted)
+        VALUES ($1, $2, true, false, true)
+        ",
+        id,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error adding team leader {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Team {id} created");
+    Ok(Json(id))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct NewTeam {
+    name: String,
+    description: Option<String>,
+}
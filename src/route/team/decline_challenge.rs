@@ -0,0 +1,47 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Decline a challenge to the team we lead, or withdraw one it made
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Declining match {id}");
+
+    let declined = sqlx::query!(
+        "
+        DELETE FROM games.t_team_matches m
+        WHERE id = $1
+          AND accepted_at IS NULL
+          AND EXISTS (
+            SELECT
+            FROM users.team_members l
+            WHERE l.user_id = $2
+              AND l.leader
+              AND l.team_id IN (m.challenger, m.challenged)
+          )
+        ",
+        id,
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error declining match {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if declined.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::OK)
+}
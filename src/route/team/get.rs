@@ -0,0 +1,168 @@
+use crate::team;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// A team's page: its members and its matches, newest first
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<Json<Team>, StatusCode> {
+    let details = sqlx::query!(
+        "
+        SELECT name, description, created_at
+        FROM users.teams
+        WHERE id = $1
+        ",
+        id,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting team {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let members = sqlx::query_as!(
+        TeamMember,
+        r#"
+        SELECT u.username, m.leader, m.created_at as joined_at
+        FROM users.team_members m
+            JOIN users.basic_info u ON u.id = m.user_id
+        WHERE m.team_id = $1
+          AND m.accepted
+        ORDER BY m.leader DESC, m.created_at
+        "#,
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting team members {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let matches = sqlx::query!(
+        r#"
+        SELECT m.id, m.challenger, ca.name as "challenger_name?", m.challenged,
+            cd.name as "challenged_name?", m.boards, m.created_at, m.accepted_at
+        FROM games.t_team_matches m
+            LEFT JOIN users.teams ca ON ca.id = m.challenger
+            LEFT JOIN users.teams cd ON cd.id = m.challenged
+        WHERE m.challenger = $1
+           OR m.challenged = $1
+        ORDER BY m.created_at DESC, m.id DESC
+        "#,
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting team matches {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let boards = sqlx::query!(
+        r#"
+        SELECT b.match_id, b.challenger_white, fi.result as "result?"
+        FROM games.t_match_boards b
+            JOIN games.t_team_matches m ON m.id = b.match_id
+            LEFT JOIN games.t_finished fi ON fi.id = b.id_game
+        WHERE m.challenger = $1
+           OR m.challenged = $1
+        "#,
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting match boards {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let matches = matches
+        .into_iter()
+        .map(|m| {
+            let results: Vec<(bool, Option<&str>)> = boards
+                .iter()
+                .filter(|board| board.match_id == m.id)
+                .map(|board| (board.challenger_white, board.result.as_deref()))
+                .collect();
+            let finished = results
+                .iter()
+                .filter(|(_, result)| result.is_some())
+                .count();
+            let (challenger_score, challenged_score) = team::score(&results);
+            Match {
+                id: m.id,
+                challenger: Side {
+                    id: m.challenger,
+                    name: m.challenger_name,
+                    score: challenger_score,
+                },
+                challenged: Side {
+                    id: m.challenged,
+                    name: m.challenged_name,
+                    score: challenged_score,
+                },
+                boards: m.boards,
+                status: super::status(m.accepted_at.is_some(), finished, m.boards as usize),
+                created_at: m.created_at,
+            }
+        })
+        .collect();
+
+    Ok(Json(Team {
+        id,
+        name: details.name,
+        description: details.description,
+        created_at: details.created_at,
+        members,
+        matches,
+    }))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Team {
+    id: i64,
+    name: String,
+    description: String,
+    created_at: NaiveDateTime,
+    /// Leader first
+    members: Vec<TeamMember>,
+    matches: Vec<Match>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct TeamMember {
+    username: String,
+    leader: bool,
+    joined_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Match {
+    id: i64,
+    challenger: Side,
+    challenged: Side,
+    boards: i32,
+    status: &'static str,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Side {
+    /// Missing once the team disbanded
+    id: Option<i64>,
+    name: Option<String>,
+    /// Points from the boards finished so far
+    score: f64,
+}
@@ -0,0 +1,121 @@
+use crate::team;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// A match with the game of every board and the score so far
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<Json<Match>, StatusCode> {
+    let details = sqlx::query!(
+        r#"
+        SELECT m.challenger, ca.name as "challenger_name?", m.challenged,
+            cd.name as "challenged_name?", m.boards, m.created_at, m.accepted_at
+        FROM games.t_team_matches m
+            LEFT JOIN users.teams ca ON ca.id = m.challenger
+            LEFT JOIN users.teams cd ON cd.id = m.challenged
+        WHERE m.id = $1
+        "#,
+        id,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting match {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Games are active or finished, players come from either
+    let boards = sqlx::query_as!(
+        Board,
+        r#"
+        SELECT b.board, b.id_game, b.challenger_white,
+            COALESCE(fi.player_w, ac.player_w) as "white!",
+            COALESCE(fi.player_b, ac.player_b) as "black!",
+            fi.result as "result?"
+        FROM games.t_match_boards b
+            LEFT JOIN games.t_finished fi ON fi.id = b.id_game
+            LEFT JOIN games.t_active ac ON ac.id = b.id_game
+        WHERE b.match_id = $1
+        ORDER BY b.board
+        "#,
+        id,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting match boards {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let results: Vec<(bool, Option<&str>)> = boards
+        .iter()
+        .map(|board| (board.challenger_white, board.result.as_deref()))
+        .collect();
+    let finished = results
+        .iter()
+        .filter(|(_, result)| result.is_some())
+        .count();
+    let (challenger_score, challenged_score) = team::score(&results);
+
+    Ok(Json(Match {
+        id,
+        challenger: Side {
+            id: details.challenger,
+            name: details.challenger_name,
+            score: challenger_score,
+        },
+        challenged: Side {
+            id: details.challenged,
+            name: details.challenged_name,
+            score: challenged_score,
+        },
+        status: super::status(
+            details.accepted_at.is_some(),
+            finished,
+            details.boards as usize,
+        ),
+        created_at: details.created_at,
+        accepted_at: details.accepted_at,
+        boards,
+    }))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Match {
+    id: i64,
+    challenger: Side,
+    challenged: Side,
+    status: &'static str,
+    created_at: NaiveDateTime,
+    accepted_at: Option<NaiveDateTime>,
+    boards: Vec<Board>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Side {
+    /// Missing once the team disbanded
+    id: Option<i64>,
+    name: Option<String>,
+    /// Points from the boards finished so far
+    score: f64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Board {
+    board: i32,
+    id_game: i64,
+    challenger_white: bool,
+    white: String,
+    black: String,
+    result: Option<String>,
+}
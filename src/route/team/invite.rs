@@ -0,0 +1,92 @@
+use super::Member;
+use crate::{authentication::LoggedUser, social, team};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+/// Invite a user to the team we lead. Inviting someone who asked to join
+/// approves their request.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+    Json(payload): Json<Member>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Inviting {} to team {id}", payload.username);
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    team::check_leader(&mut trx, id, user.id()).await?;
+
+    if social::blocked_between(&mut trx, user.username(), &payload.username).await? {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let approved = sqlx::query!(
+        "
+        UPDATE users.team_members m
+        SET accepted = true
+        FROM users.basic_info u
+        WHERE u.id = m.user_id
+          AND u.username = $2
+          AND m.team_id = $1
+          AND NOT m.invited
+          AND NOT m.accepted
+        ",
+        id,
+        payload.username,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error approving team request {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if approved == 0 {
+        let affected = sqlx::query!(
+            "
+            INSERT INTO users.team_members(team_id, user_id, invited)
+            SELECT $1, id, true
+            FROM users.basic_info
+            WHERE username = $2
+            ",
+            id,
+            payload.username,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            if err
+                .as_database_error()
+                .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
+            {
+                StatusCode::NOT_ACCEPTABLE
+            } else {
+                error!("Error inviting to team {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?
+        .rows_affected();
+
+        if affected == 0 {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,72 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+/// Ask to join a team, for its leader to approve. Asking after being
+/// invited accepts the invitation.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Joining team {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let accepted = sqlx::query!(
+        "
+        UPDATE users.team_members
+        SET accepted = true
+        WHERE team_id = $1
+          AND user_id = $2
+          AND invited
+          AND NOT accepted
+        ",
+        id,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error accepting team invitation {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if accepted == 0 {
+        sqlx::query!(
+            "
+            INSERT INTO users.team_members(team_id, user_id, invited)
+            VALUES ($1, $2, false)
+            ",
+            id,
+            user.id(),
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| match err.as_database_error().map(|err| err.kind()) {
+            Some(ErrorKind::UniqueViolation) => StatusCode::NOT_ACCEPTABLE,
+            Some(ErrorKind::ForeignKeyViolation) => StatusCode::NOT_FOUND,
+            _ => {
+                error!("Error asking to join team {err}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
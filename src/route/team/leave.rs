@@ -0,0 +1,94 @@
+use crate::{authentication::LoggedUser, team};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Leave a team, or drop an invitation or a request to join it. The leader
+/// can only leave last, which disbands the team.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Leaving team {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let membership = sqlx::query!(
+        r#"
+        SELECT leader,
+            (
+                SELECT count(*)
+                FROM users.team_members o
+                WHERE o.team_id = m.team_id
+                  AND o.accepted
+            ) as "members!"
+        FROM users.team_members m
+        WHERE team_id = $1
+          AND user_id = $2
+        FOR UPDATE
+        "#,
+        id,
+        user.id(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting team membership {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if membership.leader {
+        if membership.members > 1 {
+            return Err(StatusCode::NOT_ACCEPTABLE);
+        }
+
+        sqlx::query!(
+            "
+            DELETE FROM users.teams
+            WHERE id = $1
+            ",
+            id,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error disbanding team {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        team::drop_orphan_challenges(&mut trx).await?;
+        info!("Team {id} disbanded");
+    } else {
+        sqlx::query!(
+            "
+            DELETE FROM users.team_members
+            WHERE team_id = $1
+              AND user_id = $2
+            ",
+            id,
+            user.id(),
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error leaving team {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
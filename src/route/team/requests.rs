@@ -0,0 +1,54 @@
+use crate::{authentication::LoggedUser, team};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Pending invitations and requests to join the team we lead
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<Json<Vec<Request>>, StatusCode> {
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error getting connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    team::check_leader(&mut conn, id, user.id()).await?;
+
+    let requests = sqlx::query_as!(
+        Request,
+        "
+        SELECT u.username, m.invited, m.created_at
+        FROM users.team_members m
+            JOIN users.basic_info u ON u.id = m.user_id
+        WHERE m.team_id = $1
+          AND NOT m.accepted
+        ORDER BY m.created_at
+        ",
+        id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting team requests {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(requests))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Request {
+    username: String,
+    /// Invited by the team, otherwise asking to join
+    invited: bool,
+    created_at: NaiveDateTime,
+}
@@ -0,0 +1,15 @@
+//! Round robin and Swiss tournaments between club members
+
+mod create;
+mod get;
+mod join;
+mod leave;
+mod list;
+mod start;
+
+pub(crate) use create::handler as create;
+pub(crate) use get::handler as get;
+pub(crate) use join::handler as join;
+pub(crate) use leave::handler as leave;
+pub(crate) use list::handler as list;
+pub(crate) use start::handler as start;
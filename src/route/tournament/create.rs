@@ -0,0 +1,70 @@
+use crate::{authentication::LoggedUser, tournament::pairing::Format};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::ops::RangeInclusive;
+use tracing::{error, info};
+
+const NAME_LENGTH: RangeInclusive<usize> = 1..=100;
+const MINUTES: RangeInclusive<i32> = 1..=180;
+const INCREMENT: RangeInclusive<i32> = 0..=180;
+const SWISS_ROUNDS: RangeInclusive<i32> = 1..=20;
+
+/// Open a tournament for players to join, returning its id. Swiss
+/// tournaments need their number of rounds, a round robin has as many as
+/// its players need to all meet.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<NewTournament>,
+) -> Result<Json<i64>, StatusCode> {
+    info!("Creating tournament");
+
+    let name = payload.name.trim();
+    let rounds = match (payload.format, payload.rounds) {
+        (Format::RoundRobin, None) => 0,
+        (Format::Swiss, Some(rounds)) if SWISS_ROUNDS.contains(&rounds) => rounds,
+        _ => return Err(StatusCode::UNPROCESSABLE_ENTITY),
+    };
+    if !NAME_LENGTH.contains(&name.chars().count())
+        || !MINUTES.contains(&payload.minutes)
+        || !INCREMENT.contains(&payload.increment)
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let id = sqlx::query_scalar!(
+        "
+        INSERT INTO games.t_tournaments(name, organizer, format, minutes, increment, rounds)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id
+        ",
+        name,
+        user.username(),
+        payload.format.as_str(),
+        payload.minutes,
+        payload.increment,
+        rounds,
+    )
+    .fetch_one(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error creating tournament {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Tournament {id} created");
+    Ok(Json(id))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct NewTournament {
+    name: String,
+    format: Format,
+    /// Minutes per player
+    minutes: i32,
+    /// Seconds added per move
+    increment: i32,
+    rounds: Option<i32>,
+}
@@ -0,0 +1,142 @@
+use crate::tournament::{
+    self,
+    pairing::{self, Format},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// A tournament with its players, standings and pairings
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Path(id): Path<i64>,
+) -> Result<Json<Tournament>, StatusCode> {
+    let mut conn = postgres.acquire().await.map_err(|err| {
+        error!("Error getting connection {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let details = sqlx::query!(
+        "
+        SELECT name, organizer, format, minutes, increment, rounds, current_round, status
+        FROM games.t_tournaments
+        WHERE id = $1
+        ",
+        id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournament {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    let format = Format::parse(&details.format).ok_or_else(|| {
+        error!("Tournament {id} has an unknown format");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let (entrants, played) = tournament::entrants(&mut conn, id).await?;
+    let records = tournament::records(&entrants, &played, format);
+
+    let standings = pairing::standings(&records)
+        .into_iter()
+        .enumerate()
+        .map(|(rank, standing)| Standing {
+            rank: rank + 1,
+            username: entrants[standing.seed].username.clone(),
+            points: standing.points,
+            buchholz: standing.buchholz,
+            sonneborn_berger: standing.sonneborn_berger,
+        })
+        .collect();
+    let pairings = played
+        .into_iter()
+        .map(|played| Pairing {
+            round: played.round,
+            board: played.board,
+            white: entrants[played.pairing.white].username.clone(),
+            black: played
+                .pairing
+                .black
+                .map(|seed| entrants[seed].username.clone()),
+            id_game: played.id_game,
+            result: played.pairing.result,
+        })
+        .collect();
+    let players = entrants
+        .into_iter()
+        .map(|entrant| Player {
+            username: entrant.username,
+            rating: entrant.rating,
+            withdrawn: entrant.withdrawn,
+        })
+        .collect();
+
+    Ok(Json(Tournament {
+        id,
+        name: details.name,
+        organizer: details.organizer,
+        format,
+        minutes: details.minutes,
+        increment: details.increment,
+        rounds: details.rounds,
+        current_round: details.current_round,
+        status: details.status,
+        players,
+        standings,
+        pairings,
+    }))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Tournament {
+    id: i64,
+    name: String,
+    organizer: String,
+    format: Format,
+    minutes: i32,
+    increment: i32,
+    rounds: i32,
+    current_round: i32,
+    status: String,
+    /// In seeding order
+    players: Vec<Player>,
+    standings: Vec<Standing>,
+    pairings: Vec<Pairing>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Player {
+    username: String,
+    /// Rating when joining
+    rating: i32,
+    withdrawn: bool,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Standing {
+    rank: usize,
+    username: String,
+    points: f64,
+    buchholz: f64,
+    sonneborn_berger: f64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Pairing {
+    round: i32,
+    board: i32,
+    white: String,
+    /// None for a bye
+    black: Option<String>,
+    /// None while the game is played, also for a bye
+    id_game: Option<i64>,
+    result: Option<String>,
+}
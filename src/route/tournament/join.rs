@@ -0,0 +1,76 @@
+use crate::authentication::LoggedUser;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::{error::ErrorKind, PgPool};
+use tracing::{error, info};
+
+/// Join a tournament that has not started, seeded by the current rating
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Joining tournament {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Locked so the tournament cannot start while joining
+    let status = sqlx::query_scalar!(
+        "
+        SELECT status
+        FROM games.t_tournaments
+        WHERE id = $1
+        FOR SHARE
+        ",
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournament {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    if status != "open" {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    // Joining twice is a unique violation
+    sqlx::query!(
+        "
+        INSERT INTO games.t_tournament_players(tournament_id, user_id, username, rating)
+        SELECT $1, id, username, rating
+        FROM users.basic_info
+        WHERE id = $2
+        ",
+        id,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        if err
+            .as_database_error()
+            .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
+        {
+            StatusCode::NOT_ACCEPTABLE
+        } else {
+            error!("Error joining tournament {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,54 @@
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Tournaments listed, newest first
+const LIMIT: i64 = 100;
+
+/// Tournaments open or running, then the latest finished ones
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+) -> Result<Json<Vec<Tournament>>, StatusCode> {
+    let tournaments = sqlx::query_as!(
+        Tournament,
+        r#"
+        SELECT t.id, t.name, t.organizer, t.format, t.minutes, t.increment, t.rounds,
+            t.current_round, t.status, t.created_at,
+            (
+                SELECT count(*)
+                FROM games.t_tournament_players pl
+                WHERE pl.tournament_id = t.id
+            ) as "players!"
+        FROM games.t_tournaments t
+        ORDER BY t.status = 'finished', t.created_at DESC
+        LIMIT $1
+        "#,
+        LIMIT,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournaments {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(tournaments))
+}
+
+#[derive(Serialize)]
+pub(crate) struct Tournament {
+    id: i64,
+    name: String,
+    organizer: String,
+    format: String,
+    minutes: i32,
+    increment: i32,
+    rounds: i32,
+    current_round: i32,
+    status: String,
+    created_at: DateTime<Utc>,
+    players: i64,
+}
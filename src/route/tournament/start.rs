@@ -0,0 +1,66 @@
+use crate::{authentication::LoggedUser, hub::Hub, tournament};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+
+/// Start a tournament, pairing its first round. Only its organizer can, once
+/// at least two players joined.
+#[tracing::instrument(skip(hub))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Starting tournament {id}");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let tournament = sqlx::query!(
+        r#"
+        SELECT organizer, status,
+            (
+                SELECT count(*)
+                FROM games.t_tournament_players pl
+                WHERE pl.tournament_id = t.id
+            ) as "players!"
+        FROM games.t_tournaments t
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournament {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if &tournament.organizer != user.username() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if tournament.status != "open" || tournament.players < 2 {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    let starts = tournament::next_round(&mut trx, id).await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tournament::announce(&hub, starts);
+
+    Ok(StatusCode::OK)
+}
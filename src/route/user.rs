@@ -1,2 +1,14 @@
+pub mod block;
+pub mod bot;
+pub mod create_token;
+pub mod delete;
+pub mod export;
 pub mod get;
+pub mod password;
 pub mod post;
+pub mod refresh;
+pub mod revoke_token;
+pub mod settings;
+pub mod stats;
+pub mod tokens;
+pub mod unblock;
@@ -0,0 +1,98 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Block>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Blocking {}", payload.username);
+
+    if &payload.username == user.username() {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let affected = sqlx::query!(
+        "
+        INSERT INTO users.blocks(blocker_id, blocked_id)
+        SELECT $1, id
+        FROM users.basic_info
+        WHERE username = $2
+        ON CONFLICT DO NOTHING
+        ",
+        user.id(),
+        payload.username,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error blocking user {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        // Either the user does not exist or it is already blocked
+        let exists = sqlx::query!(
+            "
+            SELECT id
+            FROM users.basic_info
+            WHERE username = $1
+            ",
+            payload.username,
+        )
+        .fetch_optional(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error getting user {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .is_some();
+
+        if !exists {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    }
+
+    // Blocking someone also ends the friendship
+    sqlx::query!(
+        "
+        DELETE FROM users.friends f
+        USING users.basic_info u
+        WHERE u.username = $1
+          AND (
+            (f.requester_id = $2 AND f.addressee_id = u.id)
+            OR (f.requester_id = u.id AND f.addressee_id = $2)
+          )
+        ",
+        payload.username,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error removing friend {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Block {
+    pub(super) username: String,
+}
@@ -0,0 +1,49 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Turn the account into a bot account, for good. Only accounts that never
+/// played can, so bots do not inherit a person's games and rating.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Upgrading to a bot account");
+
+    let upgraded = sqlx::query!(
+        "
+        UPDATE users.basic_info u
+        SET bot_account = true
+        WHERE id = $1
+          AND engine_depth IS NULL
+          AND NOT EXISTS (
+            SELECT
+            FROM games.t_active
+            WHERE player_w = u.username
+               OR player_b = u.username
+          )
+          AND NOT EXISTS (
+            SELECT
+            FROM games.t_finished
+            WHERE (player_w = u.username AND NOT guest_w)
+               OR (player_b = u.username AND NOT guest_b)
+          )
+        ",
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error upgrading to a bot account {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if upgraded == 0 {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,58 @@
+use crate::authentication::{generate_token, LoggedUser};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// Issue an API token for a bot account. The token is only shown here.
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<NewToken>,
+) -> Result<Json<ApiToken>, StatusCode> {
+    info!("Creating API token");
+
+    if payload.name.trim().is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let token = sqlx::query_as!(
+        ApiToken,
+        "
+        INSERT INTO users.api_tokens(token, name, user_id)
+        SELECT $1, $2, id
+        FROM users.basic_info
+        WHERE id = $3
+          AND bot_account
+        RETURNING id, token, name, created_at
+        ",
+        generate_token(),
+        payload.name.trim(),
+        user.id(),
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error creating API token {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::FORBIDDEN)?;
+
+    Ok(Json(token))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct NewToken {
+    /// Reminder of what the token is for
+    name: String,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct ApiToken {
+    id: i64,
+    token: String,
+    name: String,
+    created_at: DateTime<Utc>,
+}
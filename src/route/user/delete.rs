@@ -0,0 +1,393 @@
+use crate::{
+    authentication::{self, is_pass_equivalent, LoggedUser},
+    game::{self, Outcome},
+    hub::{GameEvent, Hub},
+    team,
+    validation::DELETED_USERNAME,
+};
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{error, info};
+
+#[tracing::instrument(skip(hub, payload))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(hub): State<Arc<Hub>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<DeleteUser>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Deleting account");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = sqlx::query!(
+        "
+        SELECT password
+        FROM users.basic_info
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        user.id(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting user's password {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if !is_pass_equivalent(&payload.password, &current.password) {
+        return Err(StatusCode::NOT_ACCEPTABLE);
+    }
+
+    // Resign every active game, they reference the user
+    let active = sqlx::query!(
+        "
+        SELECT id, player_w
+        FROM games.t_active
+        WHERE player_w = $1
+           OR player_b = $1
+        ",
+        user.username(),
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting active games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let mut resigned = Vec::with_capacity(active.len());
+    for game in active {
+        let outcome = if &game.player_w == user.username() {
+            Outcome::BlackWins
+        } else {
+            Outcome::WhiteWins
+        };
+        game::finish(&mut trx, game.id, outcome).await?;
+        resigned.push((game.id, outcome));
+    }
+
+    // Keep finished games for the opponents, but without the user's name
+    sqlx::query!(
+        "
+        UPDATE games.t_finished
+        SET player_w = CASE WHEN player_w = $1 AND NOT guest_w THEN $2 ELSE player_w END,
+            player_b = CASE WHEN player_b = $1 AND NOT guest_b THEN $2 ELSE player_b END
+        WHERE (player_w = $1 AND NOT guest_w)
+           OR (player_b = $1 AND NOT guest_b)
+        ",
+        user.username(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error anonymising finished games {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_chat
+        SET author = $2
+        WHERE author = $1
+        ",
+        user.username(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error anonymising chat messages {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_daily_results
+        WHERE username = $1
+        ",
+        user.username(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting results {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Leave tournaments not started yet, withdraw from the others
+    sqlx::query!(
+        "
+        DELETE FROM games.t_tournament_players pl
+        USING games.t_tournaments t
+        WHERE t.id = pl.tournament_id
+          AND t.status = 'open'
+          AND pl.user_id = $1
+        ",
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error leaving tournaments {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_tournament_players
+        SET username = $2,
+            withdrawn = true
+        WHERE user_id = $1
+        ",
+        user.id(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error withdrawing from tournaments {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Tournaments the user organized and nobody can start anymore
+    sqlx::query!(
+        "
+        DELETE FROM games.t_tournaments
+        WHERE organizer = $1
+          AND status = 'open'
+        ",
+        user.username(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting tournaments {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_tournaments
+        SET organizer = $2
+        WHERE organizer = $1
+        ",
+        user.username(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error anonymising tournaments {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Same for arenas, where the user's points stay in the standings
+    sqlx::query!(
+        "
+        DELETE FROM games.t_arena_players pl
+        USING games.t_arenas a
+        WHERE a.id = pl.arena_id
+          AND a.status = 'open'
+          AND pl.user_id = $1
+        ",
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error leaving arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arena_players
+        SET username = $2,
+            paused = true
+        WHERE user_id = $1
+        ",
+        user.id(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error pausing arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM games.t_arenas
+        WHERE organizer = $1
+          AND status = 'open'
+        ",
+        user.username(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE games.t_arenas
+        SET organizer = $2
+        WHERE organizer = $1
+        ",
+        user.username(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error anonymising arenas {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Teams the user leads pass to their oldest member, or are disbanded
+    let led = sqlx::query_scalar!(
+        "
+        UPDATE users.team_members
+        SET leader = false
+        WHERE user_id = $1
+          AND leader
+        RETURNING team_id
+        ",
+        user.id(),
+    )
+    .fetch_all(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error stepping down as team leader {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        UPDATE users.team_members m
+        SET leader = true
+        FROM (
+            SELECT DISTINCT ON (team_id) team_id, user_id
+            FROM users.team_members
+            WHERE team_id = ANY($1)
+              AND user_id <> $2
+              AND accepted
+            ORDER BY team_id, created_at
+        ) heir
+        WHERE m.team_id = heir.team_id
+          AND m.user_id = heir.user_id
+        ",
+        &led,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error passing team leadership {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM users.teams t
+        WHERE id = ANY($1)
+          AND NOT EXISTS (
+            SELECT
+            FROM users.team_members m
+            WHERE m.team_id = t.id
+              AND m.leader
+          )
+        ",
+        &led,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error disbanding teams {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    team::drop_orphan_challenges(&mut trx).await?;
+
+    // Lineups name players rather than reference them
+    sqlx::query!(
+        "
+        UPDATE games.t_team_matches
+        SET lineup = array_replace(lineup, $1, $2)
+        WHERE $1 = ANY(lineup)
+        ",
+        user.username(),
+        DELETED_USERNAME,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error anonymising lineups {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    sqlx::query!(
+        "
+        DELETE FROM games.v_pending_invites
+        WHERE inviter = $1
+           OR invited = $1
+        ",
+        user.username(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting invites {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    authentication::revoke_all(&mut trx, user.id()).await?;
+
+    sqlx::query!(
+        "
+        DELETE FROM users.basic_info
+        WHERE id = $1
+        ",
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error deleting user {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    for (id, outcome) in resigned {
+        hub.publish(
+            id,
+            GameEvent::Finished {
+                result: outcome.as_pgn(),
+            },
+        );
+    }
+
+    info!("User {} deleted", user.username());
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct DeleteUser {
+    password: String,
+}
@@ -0,0 +1,238 @@
+use std::{convert::Infallible, str::FromStr};
+
+use crate::{authentication::LoggedUser, pgn};
+use axum::{
+    body::StreamBody,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Result},
+    Extension,
+};
+use chess::Board;
+use chrono::{NaiveDate, NaiveDateTime};
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::error;
+
+/// Every finished game of `username`, newest first, as one PGN file or
+/// one JSON object per line. Active games are added with `active=true`;
+/// other players only get the public ones.
+#[tracing::instrument(skip(postgres))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Path(username): Path<String>,
+    Query(params): Query<Params>,
+) -> Result<impl IntoResponse> {
+    let exists = sqlx::query!(
+        "
+        SELECT
+        FROM users.basic_info
+        WHERE username = $1
+        ",
+        username,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting user {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .is_some();
+    if !exists {
+        return Err(StatusCode::NOT_FOUND.into());
+    }
+
+    let own = user.username() == &username;
+    let format = params.format;
+
+    let (content_type, extension) = match format {
+        Format::Pgn => ("application/x-chess-pgn", "pgn"),
+        Format::Ndjson => ("application/x-ndjson", "ndjson"),
+    };
+    let disposition = format!("attachment; filename=\"{username}.{extension}\"");
+
+    let (sender, receiver) = mpsc::channel::<Result<String, Infallible>>(16);
+    tokio::spawn(async move {
+        // Rows are read as they arrive instead of collecting the history
+        let mut games = sqlx::query_as!(
+            Exported,
+            r#"
+            SELECT
+                id as "id!",
+                player_w as "player_w!",
+                player_b as "player_b!",
+                start_pos as "start_pos!",
+                moves as "moves!",
+                result as "result!",
+                end_date,
+                imported as "imported!",
+                eco,
+                opening,
+                active as "active!"
+            FROM (
+                SELECT
+                    id,
+                    player_w,
+                    player_b,
+                    start_pos::text,
+                    moves,
+                    COALESCE(result, '*') as result,
+                    end_date,
+                    imported,
+                    eco,
+                    opening,
+                    false as active
+                FROM games.t_finished
+                WHERE (player_w = $1 AND NOT guest_w)
+                   OR (player_b = $1 AND NOT guest_b)
+
+                UNION ALL
+
+                SELECT
+                    ac.id,
+                    ac.player_w,
+                    ac.player_b,
+                    ac.start_pos,
+                    COALESCE(string_agg(mo.san, ' ' ORDER BY mo.move_num), ''),
+                    '*',
+                    NULL,
+                    false,
+                    NULL,
+                    NULL,
+                    true
+                FROM games.t_active ac
+                    LEFT JOIN games.t_moves mo ON mo.id_game = ac.id
+                WHERE $2
+                  AND (ac.player_w = $1 OR ac.player_b = $1)
+                  AND ($3 OR ac.public)
+                GROUP BY ac.id
+            ) g
+            WHERE ($4::date IS NULL OR COALESCE(end_date, localtimestamp) >= $4)
+              AND ($5::date IS NULL OR COALESCE(end_date, localtimestamp) < $5 + 1)
+              AND ($6::text IS NULL OR $6 IN (player_w, player_b) AND $6 <> $1)
+            ORDER BY COALESCE(end_date, localtimestamp) DESC, id DESC
+            "#,
+            username,
+            params.active,
+            own,
+            params.since,
+            params.until,
+            params.opponent,
+        )
+        .fetch(&postgres);
+
+        loop {
+            let game = match games.try_next().await {
+                Ok(Some(game)) => game,
+                Ok(None) => return,
+                Err(err) => {
+                    // Headers are gone already, the client sees a short file
+                    error!("Error exporting games {err}");
+                    return;
+                }
+            };
+
+            let text = match format {
+                Format::Pgn => game.pgn(),
+                Format::Ndjson => {
+                    let mut line = serde_json::to_string(&game).expect("games serialize");
+                    line.push('\n');
+                    line
+                }
+            };
+
+            // The client went away
+            if sender.send(Ok(text)).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        StreamBody::new(ReceiverStream::new(receiver)),
+    ))
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Format {
+    #[default]
+    Pgn,
+    Ndjson,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Params {
+    #[serde(default)]
+    format: Format,
+    /// Include games still being played
+    #[serde(default)]
+    active: bool,
+    /// First day included
+    since: Option<NaiveDate>,
+    /// Last day included
+    until: Option<NaiveDate>,
+    opponent: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Exported {
+    id: i64,
+    player_w: String,
+    player_b: String,
+    start_pos: String,
+    moves: String,
+    result: String,
+    /// Missing for active games
+    end_date: Option<NaiveDateTime>,
+    imported: bool,
+    eco: Option<String>,
+    opening: Option<String>,
+    active: bool,
+}
+
+impl Exported {
+    fn pgn(&self) -> String {
+        let moves: Vec<&str> = self.moves.split_whitespace().collect();
+
+        // Moves were checked when played, this only writes them the standard way
+        let moves = Board::from_str(&self.start_pos)
+            .ok()
+            .and_then(|start| pgn::replay(start, &moves).ok())
+            .unwrap_or_else(|| moves.iter().map(|san| san.to_string()).collect());
+
+        let event = if self.imported {
+            "Imported game"
+        } else {
+            "Casual game"
+        };
+        let date = match self.end_date {
+            Some(date) => date.format("%Y.%m.%d").to_string(),
+            None => "????.??.??".to_string(),
+        };
+        let id = self.id.to_string();
+        let mut tags = vec![
+            ("Event", event),
+            ("Site", "chess_uclv"),
+            ("Date", &date),
+            ("Round", "-"),
+            ("White", &self.player_w),
+            ("Black", &self.player_b),
+            ("Result", &self.result),
+            ("GameId", &id),
+        ];
+        if let (Some(eco), Some(opening)) = (&self.eco, &self.opening) {
+            tags.push(("ECO", eco));
+            tags.push(("Opening", opening));
+        }
+        pgn::write(&tags, &self.start_pos, &moves, &self.result)
+    }
+}
@@ -1,4 +1,9 @@
-use crate::authentication::{generate_token, is_pass_equivalent};
+use std::sync::Arc;
+
+use crate::{
+    authentication::{generate_token, is_pass_equivalent, issue_session, Session},
+    config::Config,
+};
 use axum::{
     extract::{Json, State},
     http::StatusCode,
@@ -8,12 +13,13 @@ use serde::Deserialize;
 use sqlx::PgPool;
 use tracing::{error, info};
 
-#[tracing::instrument]
+#[tracing::instrument(skip(user))]
 pub(crate) async fn handler(
     // database connection pool
     State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
     Json(user): Json<LoginAttempt>,
-) -> Result<String, StatusCode> {
+) -> Result<Json<Session>, StatusCode> {
     info!("Starting!");
     // Get user and password
     let pot_user = sqlx::query_as!(
@@ -21,9 +27,11 @@ pub(crate) async fn handler(
         "
         SELECT
             id,
+            username,
             password
         FROM users.basic_info
         WHERE username = $1
+          AND engine_depth IS NULL
         ",
         &user.username,
     )
@@ -39,30 +47,35 @@ pub(crate) async fn handler(
         return Err(StatusCode::NOT_ACCEPTABLE);
     }
 
-    let token = generate_token();
+    // Both tokens are stored, or neither
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-    sqlx::query!(
-        "
-        INSERT INTO users.token(token, user_id)
-        VALUES($1, $2)
-        ",
-        &token,
+    // Every login starts a new family of tokens
+    let session = issue_session(
+        &mut trx,
+        &config.sessions,
         pot_user.id,
+        &pot_user.username,
+        &generate_token(),
     )
-    .execute(&pool)
-    .await
-    .map_err(|err| {
-        error!("Error storing new token {err}");
+    .await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    Ok(token)
+    Ok(Json(session))
 }
 
 // the input to our handler
 #[derive(sqlx::FromRow)]
 pub(crate) struct User {
     id: i64,
+    username: String,
     password: String,
 }
 
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use crate::{
+    authentication::{self, is_pass_equivalent, LoggedUser},
+    config::Config,
+    validation,
+};
+use axum::{extract::State, http::StatusCode, response::Result, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use super::post::invalid;
+
+#[tracing::instrument(skip(payload))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<ChangePassword>,
+) -> Result<StatusCode> {
+    info!("Changing password");
+
+    // Same rules as when registering
+    if let Some(violation) = validation::password(&config.registration, &payload.new_password) {
+        return Err(invalid(vec![violation]));
+    }
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = sqlx::query!(
+        "
+        SELECT password
+        FROM users.basic_info
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        user.id(),
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting user's password {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if !is_pass_equivalent(&payload.old_password, &current.password) {
+        return Err(StatusCode::NOT_ACCEPTABLE.into());
+    }
+
+    sqlx::query!(
+        "
+        UPDATE users.basic_info
+        SET password = $1
+        WHERE id = $2
+        ",
+        payload.new_password,
+        user.id(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error updating password {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Every session, including the current one, has to log in again
+    authentication::revoke_all(&mut trx, user.id()).await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct ChangePassword {
+    old_password: String,
+    new_password: String,
+}
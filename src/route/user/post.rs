@@ -1,17 +1,56 @@
-use axum::{extract::State, http::StatusCode, Json};
-use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{config::Config, validation};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Result},
+    Json,
+};
+use serde::{Deserialize, Serialize};
 use sqlx::{error::ErrorKind, PgPool};
 use tracing::{debug, error, info};
 
-#[tracing::instrument]
+#[tracing::instrument(skip(payload))]
 pub(crate) async fn handler(
     // database connection pool
     State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
     // this argument tells axum to parse the request body
     // as JSON into a `CreateUser` type
     Json(payload): Json<CreateUser>,
-) -> Result<StatusCode, StatusCode> {
+) -> Result<StatusCode> {
     info!("Starting!");
+
+    let mut violations =
+        validation::registration(&config.registration, &payload.username, &payload.password);
+
+    // Usernames are unique regardless of case
+    let taken = sqlx::query!(
+        "
+        SELECT id
+        FROM users.basic_info
+        WHERE lower(username) = lower($1)
+        ",
+        &payload.username,
+    )
+    .fetch_optional(&pool)
+    .await
+    .map_err(|err| {
+        error!("Error checking username {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .is_some();
+
+    if taken {
+        violations.push(validation::username_taken());
+    }
+
+    if !violations.is_empty() {
+        debug!("Rejected registration of {}", payload.username);
+        return Err(invalid(violations));
+    }
+
     // Store information in the database
     sqlx::query!(
         "
@@ -30,10 +69,10 @@ pub(crate) async fn handler(
             .is_some_and(|err| err.kind() == ErrorKind::UniqueViolation)
         {
             debug!("User {} already exists", payload.username);
-            StatusCode::NOT_ACCEPTABLE
+            invalid(vec![validation::username_taken()])
         } else {
             error!("Error inserting to database: {err}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            StatusCode::INTERNAL_SERVER_ERROR.into()
         }
     })
     .map(|_| {
@@ -42,9 +81,20 @@ pub(crate) async fn handler(
     })
 }
 
+pub(super) fn invalid(errors: Vec<validation::Violation>) -> axum::response::ErrorResponse {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(Invalid { errors }))
+        .into_response()
+        .into()
+}
+
 // the input to our `create_user` handler
 #[derive(Deserialize, Debug)]
 pub(crate) struct CreateUser {
     username: String,
     password: String,
 }
+
+#[derive(Serialize)]
+pub(crate) struct Invalid {
+    errors: Vec<validation::Violation>,
+}
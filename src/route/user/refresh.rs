@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use crate::{
+    authentication::{issue_session, Session},
+    config::Config,
+};
+use axum::{extract::State, http::StatusCode, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+#[tracing::instrument(skip(payload))]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    Json(payload): Json<Refresh>,
+) -> Result<Json<Session>, StatusCode> {
+    info!("Refreshing session");
+
+    let mut trx = postgres.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let current = sqlx::query!(
+        r#"
+        SELECT
+            r.user_id,
+            u.username,
+            r.family,
+            r.used,
+            r.expiration > now() as "valid!"
+        FROM users.refresh_token r
+            JOIN users.basic_info u ON u.id = r.user_id
+        WHERE r.token = $1
+        FOR UPDATE OF r
+        "#,
+        payload.refresh_token,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting refresh token {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    // A refresh token is only valid once. Seeing it again means it leaked,
+    // so every token descending from the same login is revoked.
+    if current.used {
+        warn!("Refresh token reused, revoking family {}", current.family);
+
+        sqlx::query!(
+            "
+            DELETE FROM users.token
+            WHERE family = $1
+            ",
+            current.family,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error revoking tokens {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        sqlx::query!(
+            "
+            DELETE FROM users.refresh_token
+            WHERE family = $1
+            ",
+            current.family,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error revoking refresh tokens {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        trx.commit().await.map_err(|err| {
+            error!("Error commiting transaction {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    if !current.valid {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    sqlx::query!(
+        "
+        UPDATE users.refresh_token
+        SET used = true
+        WHERE token = $1
+        ",
+        payload.refresh_token,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error marking refresh token as used {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let session = issue_session(
+        &mut trx,
+        &config.sessions,
+        current.user_id,
+        &current.username,
+        &current.family,
+    )
+    .await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(session))
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Refresh {
+    refresh_token: String,
+}
@@ -0,0 +1,42 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<RevokeToken>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Revoking API token");
+
+    let revoked = sqlx::query!(
+        "
+        DELETE FROM users.api_tokens
+        WHERE id = $1
+          AND user_id = $2
+        ",
+        payload.id,
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error revoking API token {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if revoked == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct RevokeToken {
+    id: i64,
+}
@@ -0,0 +1,38 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Settings>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Updating settings");
+
+    sqlx::query!(
+        "
+        UPDATE users.basic_info
+        SET friends_only_invites = COALESCE($1, friends_only_invites)
+        WHERE id = $2
+        ",
+        payload.friends_only_invites,
+        user.id(),
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error updating settings {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::OK)
+}
+
+/// Fields left out keep their current value
+#[derive(Deserialize, Debug)]
+pub(crate) struct Settings {
+    friends_only_invites: Option<bool>,
+}
@@ -0,0 +1,102 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// Rating, results and the openings played with each colour
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Path(username): Path<String>,
+) -> Result<Json<Stats>, StatusCode> {
+    // Imported games count for openings but not for the rated results
+    let totals = sqlx::query!(
+        r#"
+        SELECT
+            u.rating,
+            u.puzzle_rating,
+            COALESCE(SUM(d.wins), 0)::int as "wins!",
+            COALESCE(SUM(d.draws), 0)::int as "draws!",
+            COALESCE(SUM(d.losses), 0)::int as "losses!"
+        FROM users.basic_info u
+            LEFT JOIN games.t_daily_results d ON d.username = u.username
+        WHERE u.username = $1
+        GROUP BY u.username, u.rating, u.puzzle_rating
+        "#,
+        username,
+    )
+    .fetch_optional(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting results {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let openings = sqlx::query_as!(
+        OpeningStats,
+        r#"
+        SELECT
+            eco as "eco!",
+            opening as "name!",
+            (player_w = $1 AND NOT guest_w) as "white!",
+            COUNT(*)::int as "games!",
+            COUNT(*) FILTER (
+                WHERE result = CASE WHEN player_w = $1 AND NOT guest_w THEN '1-0' ELSE '0-1' END
+            )::int as "wins!",
+            COUNT(*) FILTER (WHERE result = '1/2-1/2')::int as "draws!",
+            COUNT(*) FILTER (
+                WHERE result = CASE WHEN player_w = $1 AND NOT guest_w THEN '0-1' ELSE '1-0' END
+            )::int as "losses!"
+        FROM games.t_finished
+        WHERE ((player_w = $1 AND NOT guest_w) OR (player_b = $1 AND NOT guest_b))
+          AND eco IS NOT NULL
+        GROUP BY 1, 2, 3
+        ORDER BY 4 DESC, 1, 2
+        "#,
+        username,
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting openings {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(Stats {
+        username,
+        rating: totals.rating,
+        puzzle_rating: totals.puzzle_rating,
+        wins: totals.wins,
+        draws: totals.draws,
+        losses: totals.losses,
+        openings,
+    }))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct OpeningStats {
+    eco: String,
+    name: String,
+    /// Whether the user had the white pieces
+    white: bool,
+    games: i32,
+    wins: i32,
+    draws: i32,
+    losses: i32,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Stats {
+    username: String,
+    rating: i32,
+    puzzle_rating: i32,
+    wins: i32,
+    draws: i32,
+    losses: i32,
+    openings: Vec<OpeningStats>,
+}
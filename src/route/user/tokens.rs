@@ -0,0 +1,39 @@
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::error;
+
+/// API tokens of the account, without their values
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+) -> Result<Json<Vec<TokenInfo>>, StatusCode> {
+    let tokens = sqlx::query_as!(
+        TokenInfo,
+        "
+        SELECT id, name, created_at
+        FROM users.api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at
+        ",
+        user.id(),
+    )
+    .fetch_all(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error getting API tokens {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(tokens))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub(crate) struct TokenInfo {
+    id: i64,
+    name: String,
+    created_at: DateTime<Utc>,
+}
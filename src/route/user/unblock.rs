@@ -0,0 +1,39 @@
+use super::block::Block;
+use crate::authentication::LoggedUser;
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use sqlx::PgPool;
+use tracing::{error, info};
+
+#[tracing::instrument]
+pub(crate) async fn handler(
+    State(postgres): State<PgPool>,
+    Extension(user): Extension<LoggedUser>,
+    Json(payload): Json<Block>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Unblocking {}", payload.username);
+
+    let affected = sqlx::query!(
+        "
+        DELETE FROM users.blocks bl
+        USING users.basic_info u
+        WHERE u.id = bl.blocked_id
+          AND bl.blocker_id = $1
+          AND u.username = $2
+        ",
+        user.id(),
+        payload.username,
+    )
+    .execute(&postgres)
+    .await
+    .map_err(|err| {
+        error!("Error unblocking user {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .rows_affected();
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::OK)
+}
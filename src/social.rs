@@ -0,0 +1,67 @@
+use axum::http::StatusCode;
+use sqlx::PgConnection;
+use tracing::error;
+
+/// Whether either of the two users blocked the other
+pub(crate) async fn blocked_between(
+    conn: &mut PgConnection,
+    a: &str,
+    b: &str,
+) -> Result<bool, StatusCode> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT
+            FROM users.blocks bl
+                JOIN users.basic_info u1 ON u1.id = bl.blocker_id
+                JOIN users.basic_info u2 ON u2.id = bl.blocked_id
+            WHERE (u1.username = $1 AND u2.username = $2)
+               OR (u1.username = $2 AND u2.username = $1)
+        ) as "blocked!"
+        "#,
+        a,
+        b,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error checking blocks {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// Whether `invited` accepts invites from `inviter`, given its settings
+pub(crate) async fn accepts_invites_from(
+    conn: &mut PgConnection,
+    inviter: &str,
+    invited: &str,
+) -> Result<bool, StatusCode> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT
+            NOT u.friends_only_invites
+            OR EXISTS (
+                SELECT
+                FROM users.friends f
+                    JOIN users.basic_info i ON i.username = $1
+                WHERE f.accepted
+                  AND (
+                    (f.requester_id = i.id AND f.addressee_id = u.id)
+                    OR (f.requester_id = u.id AND f.addressee_id = i.id)
+                  )
+            ) as "allowed!"
+        FROM users.basic_info u
+        WHERE u.username = $2
+        "#,
+        inviter,
+        invited,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error checking invite settings {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+    // Unknown users are left for the invite itself to reject
+    .map(|allowed| allowed.unwrap_or(true))
+}
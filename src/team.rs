@@ -0,0 +1,131 @@
+//! Teams of players and the matches between them, one game per board
+
+use axum::http::StatusCode;
+use sqlx::PgConnection;
+use tracing::error;
+
+#[cfg(test)]
+mod test;
+
+/// Most boards a match can be played on
+pub(crate) const MAX_BOARDS: usize = 10;
+
+/// Whether the challenging team has white on `board`, counting from 1.
+/// Colours alternate down the boards.
+pub(crate) fn challenger_white(board: usize) -> bool {
+    board % 2 == 1
+}
+
+/// Points of the challenging and the challenged team, from whether the
+/// challenger had white on each board and the PGN result of its game.
+/// Boards still being played count nothing yet.
+pub(crate) fn score<S: AsRef<str>>(boards: &[(bool, Option<S>)]) -> (f64, f64) {
+    boards
+        .iter()
+        .fold((0.0, 0.0), |(challenger, challenged), (white, result)| {
+            let white_score = match result.as_ref().map(AsRef::as_ref) {
+                Some("1-0") => 1.0,
+                Some("0-1") => 0.0,
+                Some("1/2-1/2") => 0.5,
+                _ => return (challenger, challenged),
+            };
+            let score = if *white {
+                white_score
+            } else {
+                1.0 - white_score
+            };
+            (challenger + score, challenged + 1.0 - score)
+        })
+}
+
+/// Fail unless `user_id` leads team `id`
+pub(crate) async fn check_leader(
+    conn: &mut PgConnection,
+    id: i64,
+    user_id: i64,
+) -> Result<(), StatusCode> {
+    let leader = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT
+            FROM users.team_members
+            WHERE team_id = t.id
+              AND user_id = $2
+              AND leader
+        ) as "leader!"
+        FROM users.teams t
+        WHERE id = $1
+        "#,
+        id,
+        user_id,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting team leader {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    if leader {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// Check a lineup only has members of team `id`, each once
+pub(crate) async fn check_lineup(
+    conn: &mut PgConnection,
+    id: i64,
+    players: &[String],
+) -> Result<(), StatusCode> {
+    if players.is_empty() || players.len() > MAX_BOARDS {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let members = sqlx::query_scalar!(
+        r#"
+        SELECT count(DISTINCT u.id) as "members!"
+        FROM users.basic_info u
+            JOIN users.team_members m ON m.user_id = u.id
+        WHERE m.team_id = $1
+          AND m.accepted
+          AND u.username = ANY($2)
+        "#,
+        id,
+        players,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error checking lineup {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if members == players.len() as i64 {
+        Ok(())
+    } else {
+        Err(StatusCode::UNPROCESSABLE_ENTITY)
+    }
+}
+
+/// Delete the challenges left without a side by disbanded teams. Matches
+/// already played stay in the history of the other team.
+pub(crate) async fn drop_orphan_challenges(conn: &mut PgConnection) -> Result<(), StatusCode> {
+    sqlx::query!(
+        "
+        DELETE FROM games.t_team_matches
+        WHERE accepted_at IS NULL
+          AND (challenger IS NULL OR challenged IS NULL)
+        ",
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error deleting challenges of disbanded teams {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use super::*;
+
+#[test]
+fn alternates_colours_down_the_boards() {
+    let colours: Vec<bool> = (1..=4).map(challenger_white).collect();
+    assert_eq!(colours, vec![true, false, true, false]);
+}
+
+#[test]
+fn scores_each_board_for_its_colours() {
+    let boards = [
+        (true, Some("1-0")),
+        (false, Some("1-0")),
+        (true, Some("1/2-1/2")),
+        (false, Some("0-1")),
+    ];
+    assert_eq!(score(&boards), (2.5, 1.5));
+}
+
+#[test]
+fn leaves_unfinished_boards_out() {
+    let boards = [(true, Some("0-1")), (false, None), (true, Some("*"))];
+    assert_eq!(score(&boards), (0.0, 1.0));
+}
@@ -0,0 +1,249 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, MatchedPath, State},
+    http::{header::RETRY_AFTER, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use hyper::body::HttpBody;
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::{authentication::LoggedUser, config::ThrottlePolicy};
+
+mod bucket;
+#[cfg(test)]
+mod test;
+
+pub(crate) use bucket::TokenBuckets;
+
+/// Largest body read from a login or registration request, which is
+/// buffered before the user is known
+const MAX_LOGIN_BODY: usize = 4 * 1024;
+
+/// Failed attempts per key. Implemented in-process by [`MemoryStore`], other
+/// implementations can share the state between instances.
+#[async_trait]
+pub(crate) trait AttemptStore: Send + Sync {
+    /// Time `key` still has to wait before trying again
+    async fn blocked_for(&self, key: &str) -> Option<Duration>;
+    async fn record_failure(&self, key: &str);
+    async fn record_success(&self, key: &str);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    failures: u32,
+    last_failure: Instant,
+    blocked_until: Instant,
+}
+
+/// In-process [`AttemptStore`] with exponential backoff and lockout
+pub(crate) struct MemoryStore {
+    policy: ThrottlePolicy,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+/// Stale entries are pruned once the store holds this many keys
+const MAX_ENTRIES: usize = 100_000;
+
+impl MemoryStore {
+    pub(crate) fn new(policy: ThrottlePolicy) -> Self {
+        Self {
+            policy,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn blocked_at(&self, key: &str, now: Instant) -> Option<Duration> {
+        let entries = self.entries.lock().expect("throttle mutex poisoned");
+        entries
+            .get(key)
+            .and_then(|entry| entry.blocked_until.checked_duration_since(now))
+            .filter(|wait| !wait.is_zero())
+    }
+
+    fn fail_at(&self, key: &str, now: Instant) {
+        let mut entries = self.entries.lock().expect("throttle mutex poisoned");
+
+        if entries.len() >= MAX_ENTRIES {
+            let forget = self.policy.forget_after();
+            entries.retain(|_, entry| now.duration_since(entry.last_failure) < forget);
+        }
+
+        let entry = entries.entry(key.to_string()).or_insert(Entry {
+            failures: 0,
+            last_failure: now,
+            blocked_until: now,
+        });
+
+        // Old failures do not count anymore
+        if now.duration_since(entry.last_failure) >= self.policy.forget_after() {
+            entry.failures = 0;
+        }
+
+        entry.failures += 1;
+        entry.last_failure = now;
+        entry.blocked_until = now + self.policy.delay(entry.failures);
+    }
+
+    fn succeed(&self, key: &str) {
+        let mut entries = self.entries.lock().expect("throttle mutex poisoned");
+        entries.remove(key);
+    }
+}
+
+#[async_trait]
+impl AttemptStore for MemoryStore {
+    async fn blocked_for(&self, key: &str) -> Option<Duration> {
+        self.blocked_at(key, Instant::now())
+    }
+
+    async fn record_failure(&self, key: &str) {
+        self.fail_at(key, Instant::now())
+    }
+
+    async fn record_success(&self, key: &str) {
+        self.succeed(key)
+    }
+}
+
+impl ThrottlePolicy {
+    /// Time a key is blocked after its `failures`th consecutive failure
+    fn delay(&self, failures: u32) -> Duration {
+        if failures >= self.lockout_after {
+            return Duration::from_secs(self.lockout_secs);
+        }
+        let secs = self
+            .base_delay_secs
+            .saturating_mul(1 << (failures - 1).min(31))
+            .min(self.max_delay_secs);
+        Duration::from_secs(secs)
+    }
+
+    fn forget_after(&self) -> Duration {
+        Duration::from_secs(self.lockout_secs.max(self.max_delay_secs))
+    }
+}
+
+/// Throttle credential checks by client address and by target username
+///
+/// Responses with a client error status count as failed attempts. A
+/// successful one only clears the failures of the username.
+#[tracing::instrument(skip_all)]
+pub(crate) async fn login(
+    State(store): State<Arc<dyn AttemptStore>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Result<Response, StatusCode> {
+    // The username is needed before the handler runs, so the body is buffered
+    let (parts, mut body) = req.into_parts();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| {
+            error!("Error reading body {err}");
+            StatusCode::BAD_REQUEST
+        })?;
+        if bytes.len() + chunk.len() > MAX_LOGIN_BODY {
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    let username = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|body| body.get("username")?.as_str().map(str::to_lowercase));
+
+    let user_key = username.map(|username| format!("user:{username}"));
+    let mut keys = vec![format!("ip:{}", addr.ip())];
+    keys.extend(user_key.clone());
+
+    let mut wait = None;
+    for key in &keys {
+        wait = wait.max(store.blocked_for(key).await);
+    }
+    if let Some(wait) = wait {
+        warn!("Throttled attempt for {keys:?}");
+        return Ok(too_many_requests(wait));
+    }
+
+    let res = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    if res.status().is_client_error() {
+        for key in &keys {
+            store.record_failure(key).await;
+        }
+    } else if res.status().is_success() {
+        // Failures of the address only expire, or logging into one's own
+        // account between guesses would clear them
+        if let Some(key) = &user_key {
+            store.record_success(key).await;
+        }
+    }
+
+    Ok(res)
+}
+
+/// Limit requests of a logged user to the budget of each route
+///
+/// Must run after [`crate::authentication::auth`].
+#[tracing::instrument(skip_all)]
+pub(crate) async fn api<B>(
+    State(buckets): State<Arc<TokenBuckets>>,
+    Extension(user): Extension<LoggedUser>,
+    path: MatchedPath,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match buckets.take(user.id(), path.as_str()) {
+        Ok(()) => next.run(req).await,
+        Err(wait) => {
+            warn!(
+                "User {} is over budget on {}",
+                user.username(),
+                path.as_str()
+            );
+            let retry_after = retry_after_secs(wait);
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(RETRY_AFTER, retry_after.to_string())],
+                Json(RateLimited {
+                    error: "rate_limited",
+                    retry_after,
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RateLimited {
+    error: &'static str,
+    retry_after: u64,
+}
+
+fn too_many_requests(wait: Duration) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(RETRY_AFTER, retry_after_secs(wait).to_string())],
+    )
+        .into_response()
+}
+
+/// Round up, retrying earlier than asked would be blocked again
+fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
@@ -0,0 +1,80 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::config::{Budget, RateLimits};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token buckets per user and route
+pub(crate) struct TokenBuckets {
+    limits: RateLimits,
+    buckets: Mutex<HashMap<(i64, String), Bucket>>,
+}
+
+/// Full buckets are dropped once there are this many of them
+const MAX_BUCKETS: usize = 100_000;
+
+impl TokenBuckets {
+    pub(crate) fn new(limits: RateLimits) -> Self {
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for `user` on `route`, or return how long until one is
+    /// available
+    pub(crate) fn take(&self, user: i64, route: &str) -> Result<(), Duration> {
+        self.take_at(user, route, Instant::now())
+    }
+
+    fn take_at(&self, user: i64, route: &str, now: Instant) -> Result<(), Duration> {
+        let budget = self.limits.budget(route);
+        let mut buckets = self.buckets.lock().expect("bucket mutex poisoned");
+
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|(_, route), bucket| {
+                let budget = self.limits.budget(route);
+                refill(bucket, budget, now) < f64::from(budget.capacity)
+            });
+        }
+
+        let bucket = buckets.entry((user, route.to_string())).or_insert(Bucket {
+            tokens: f64::from(budget.capacity),
+            updated: now,
+        });
+
+        bucket.tokens = refill(bucket, budget, now);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / budget.refill_per_sec(),
+            ))
+        }
+    }
+}
+
+fn refill(bucket: &Bucket, budget: &Budget, now: Instant) -> f64 {
+    let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+    (bucket.tokens + elapsed * budget.refill_per_sec()).min(f64::from(budget.capacity))
+}
+
+impl Budget {
+    fn refill_per_sec(&self) -> f64 {
+        f64::from(self.capacity) / self.period_secs as f64
+    }
+}
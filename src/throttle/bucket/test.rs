@@ -0,0 +1,51 @@
+use std::{collections::HashMap, str::FromStr};
+
+use super::*;
+
+fn buckets() -> TokenBuckets {
+    TokenBuckets::new(RateLimits {
+        default: Budget::from_str("2/10").unwrap(),
+        routes: HashMap::from([("/invite".to_string(), Budget::from_str("1/60").unwrap())]),
+    })
+}
+
+#[test]
+fn burst_then_refill() {
+    let buckets = buckets();
+    let now = Instant::now();
+
+    assert!(buckets.take_at(1, "/active", now).is_ok());
+    assert!(buckets.take_at(1, "/active", now).is_ok());
+    assert_eq!(
+        buckets.take_at(1, "/active", now),
+        Err(Duration::from_secs(5))
+    );
+    assert!(buckets
+        .take_at(1, "/active", now + Duration::from_secs(5))
+        .is_ok());
+}
+
+#[test]
+fn buckets_are_per_user_and_route() {
+    let buckets = buckets();
+    let now = Instant::now();
+
+    assert!(buckets.take_at(1, "/invite", now).is_ok());
+    assert!(buckets.take_at(1, "/invite", now).is_err());
+    assert!(buckets.take_at(2, "/invite", now).is_ok());
+    assert!(buckets.take_at(1, "/active", now).is_ok());
+}
+
+#[test]
+fn budget_parsing() {
+    assert_eq!(
+        Budget::from_str("10/60"),
+        Ok(Budget {
+            capacity: 10,
+            period_secs: 60
+        })
+    );
+    assert!(Budget::from_str("10").is_err());
+    assert!(Budget::from_str("0/60").is_err());
+    assert!(Budget::from_str("10/0").is_err());
+}
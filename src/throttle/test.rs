@@ -0,0 +1,58 @@
+use super::*;
+
+fn store() -> MemoryStore {
+    MemoryStore::new(ThrottlePolicy {
+        base_delay_secs: 1,
+        max_delay_secs: 8,
+        lockout_after: 6,
+        lockout_secs: 60,
+    })
+}
+
+#[test]
+fn backoff_doubles_until_max() {
+    let policy = store().policy;
+    let delays: Vec<u64> = (1..=6).map(|n| policy.delay(n).as_secs()).collect();
+    assert_eq!(delays, vec![1, 2, 4, 8, 8, 60]);
+}
+
+#[test]
+fn failures_block_and_success_clears() {
+    let store = store();
+    let now = Instant::now();
+
+    assert_eq!(store.blocked_at("k", now), None);
+
+    store.fail_at("k", now);
+    store.fail_at("k", now);
+    assert_eq!(store.blocked_at("k", now), Some(Duration::from_secs(2)));
+    assert_eq!(store.blocked_at("k", now + Duration::from_secs(2)), None);
+    assert_eq!(store.blocked_at("other", now), None);
+
+    store.succeed("k");
+    assert_eq!(store.blocked_at("k", now), None);
+}
+
+#[test]
+fn lockout_after_repeated_failures() {
+    let store = store();
+    let now = Instant::now();
+
+    for _ in 0..6 {
+        store.fail_at("k", now);
+    }
+    assert_eq!(store.blocked_at("k", now), Some(Duration::from_secs(60)));
+}
+
+#[test]
+fn old_failures_are_forgotten() {
+    let store = store();
+    let now = Instant::now();
+
+    for _ in 0..5 {
+        store.fail_at("k", now);
+    }
+    let later = now + Duration::from_secs(60);
+    store.fail_at("k", later);
+    assert_eq!(store.blocked_at("k", later), Some(Duration::from_secs(1)));
+}
@@ -0,0 +1,352 @@
+//! Club tournaments. A round is paired once every game of the previous one
+//! is over, by a background task, so games finishing anywhere move their
+//! tournament on.
+
+use std::{sync::Arc, time::Duration};
+
+use axum::http::StatusCode;
+use sqlx::{PgConnection, PgPool};
+use tracing::{error, info};
+
+use crate::{
+    game::{self, Event, Outcome},
+    hub::{Hub, UserEvent},
+};
+
+use self::pairing::{Format, Pairing, Record};
+
+pub(crate) mod pairing;
+
+/// Wait between looks for rounds that are over
+const POLL: Duration = Duration::from_secs(2);
+
+/// Result stored when both players of a pairing withdrew
+const DOUBLE_FORFEIT: &str = "0-0";
+
+/// Store the result of game `id_game` if it belongs to a tournament
+pub(crate) async fn record(
+    trx: &mut PgConnection,
+    id_game: i64,
+    outcome: Outcome,
+) -> Result<(), StatusCode> {
+    sqlx::query!(
+        "
+        UPDATE games.t_pairings
+        SET result = $2
+        WHERE id_game = $1
+        ",
+        id_game,
+        outcome.as_pgn(),
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error recording tournament result {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(())
+}
+
+/// A player of a tournament
+pub(crate) struct Entrant {
+    pub(crate) id: i64,
+    pub(crate) username: String,
+    pub(crate) rating: i32,
+    pub(crate) withdrawn: bool,
+}
+
+/// A pairing as stored, between seeds
+pub(crate) struct Played {
+    pub(crate) round: i32,
+    pub(crate) board: i32,
+    pub(crate) id_game: Option<i64>,
+    pub(crate) pairing: Pairing,
+}
+
+/// Players in seeding order and every pairing so far, by round and board
+pub(crate) async fn entrants(
+    conn: &mut PgConnection,
+    id: i64,
+) -> Result<(Vec<Entrant>, Vec<Played>), StatusCode> {
+    let entrants = sqlx::query_as!(
+        Entrant,
+        "
+        SELECT id, username, rating, withdrawn
+        FROM games.t_tournament_players
+        WHERE tournament_id = $1
+        ORDER BY rating DESC, id
+        ",
+        id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournament players {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let rows = sqlx::query!(
+        "
+        SELECT round, board, white, black, id_game, result
+        FROM games.t_pairings
+        WHERE tournament_id = $1
+        ORDER BY round, board
+        ",
+        id,
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|err| {
+        error!("Error getting pairings {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let seed = |player: i64| {
+        entrants
+            .iter()
+            .position(|entrant| entrant.id == player)
+            .ok_or_else(|| {
+                error!("Pairing of tournament {id} with an unknown player {player}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })
+    };
+    let mut played = Vec::with_capacity(rows.len());
+    for row in rows {
+        played.push(Played {
+            round: row.round,
+            board: row.board,
+            id_game: row.id_game,
+            pairing: Pairing {
+                white: seed(row.white)?,
+                black: row.black.map(seed).transpose()?,
+                result: row.result,
+            },
+        });
+    }
+
+    Ok((entrants, played))
+}
+
+/// Records of every seed after the pairings so far
+pub(crate) fn records(entrants: &[Entrant], played: &[Played], format: Format) -> Vec<Record> {
+    let pairings: Vec<Pairing> = played.iter().map(|p| p.pairing.clone()).collect();
+    pairing::records(entrants.len(), &pairings, format)
+}
+
+/// Pair and start the next round of tournament `id` if the current one is
+/// over, or finish the tournament after its last round. Returns the events
+/// announcing the games started.
+pub(crate) async fn next_round(
+    trx: &mut PgConnection,
+    id: i64,
+) -> Result<Vec<UserEvent>, StatusCode> {
+    let tournament = sqlx::query!(
+        "
+        SELECT format, rounds, current_round, status
+        FROM games.t_tournaments
+        WHERE id = $1
+        FOR UPDATE
+        ",
+        id,
+    )
+    .fetch_optional(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error getting tournament {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    if tournament.status == "finished" {
+        return Ok(Vec::new());
+    }
+    let format = Format::parse(&tournament.format).ok_or_else(|| {
+        error!("Tournament {id} has an unknown format");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let (entrants, played) = entrants(trx, id).await?;
+    let pending = played.iter().any(|p| {
+        p.round == tournament.current_round
+            && p.pairing.black.is_some()
+            && p.pairing.result.is_none()
+    });
+    if pending {
+        return Ok(Vec::new());
+    }
+
+    let rounds = match format {
+        Format::RoundRobin if tournament.status == "open" => {
+            pairing::round_robin_rounds(entrants.len()) as i32
+        }
+        _ => tournament.rounds,
+    };
+    let round = tournament.current_round + 1;
+    let active: Vec<bool> = entrants.iter().map(|entrant| !entrant.withdrawn).collect();
+
+    if round > rounds || active.iter().filter(|active| **active).count() < 2 {
+        sqlx::query!(
+            "
+            UPDATE games.t_tournaments
+            SET status = 'finished'
+            WHERE id = $1
+            ",
+            id,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error finishing tournament {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        info!("Tournament {id} finished");
+        return Ok(Vec::new());
+    }
+
+    let pairings = match format {
+        Format::RoundRobin => pairing::berger(entrants.len(), round as usize),
+        Format::Swiss => pairing::swiss(&records(&entrants, &played, format), &active),
+    };
+
+    let mut starts = Vec::new();
+    for (board, pairing) in pairings.into_iter().enumerate() {
+        let white = &entrants[pairing.white];
+        let black = pairing.black.map(|seed| &entrants[seed]);
+
+        // Round robin pairings are fixed, whoever withdrew forfeits
+        let (id_game, result) = match black {
+            None => (None, None),
+            Some(black) if white.withdrawn || black.withdrawn => {
+                let result = match (white.withdrawn, black.withdrawn) {
+                    (true, true) => DOUBLE_FORFEIT,
+                    (true, false) => Outcome::BlackWins.as_pgn(),
+                    _ => Outcome::WhiteWins.as_pgn(),
+                };
+                (None, Some(result))
+            }
+            Some(black) => {
+                let (id_game, start) = game::create(
+                    trx,
+                    &white.username,
+                    &black.username,
+                    true,
+                    false,
+                    Event::Tournament(id),
+                )
+                .await?;
+                starts.push(start);
+                (Some(id_game), None)
+            }
+        };
+
+        sqlx::query!(
+            "
+            INSERT INTO games.t_pairings(tournament_id, round, board, white, black, id_game, result)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ",
+            id,
+            round,
+            board as i32 + 1,
+            white.id,
+            black.map(|black| black.id),
+            id_game,
+            result,
+        )
+        .execute(&mut *trx)
+        .await
+        .map_err(|err| {
+            error!("Error inserting pairing {err}");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    sqlx::query!(
+        "
+        UPDATE games.t_tournaments
+        SET status = 'running',
+            rounds = $2,
+            current_round = $3
+        WHERE id = $1
+        ",
+        id,
+        rounds,
+        round,
+    )
+    .execute(&mut *trx)
+    .await
+    .map_err(|err| {
+        error!("Error starting round {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Round {round} of tournament {id} paired");
+    Ok(starts)
+}
+
+/// Tell both players of each game that it started. Must be called once the
+/// round is committed.
+pub(crate) fn announce(hub: &Hub, starts: Vec<UserEvent>) {
+    for start in starts {
+        if let UserEvent::GameStart {
+            player_w, player_b, ..
+        } = &start
+        {
+            hub.publish_user(player_w, start.clone());
+            hub.publish_user(player_b, start.clone());
+        }
+    }
+}
+
+/// Pair the next rounds of running tournaments as their games finish
+pub(crate) async fn direct(pool: PgPool, hub: Arc<Hub>) {
+    loop {
+        tokio::time::sleep(POLL).await;
+
+        let due = sqlx::query_scalar!(
+            "
+            SELECT id
+            FROM games.t_tournaments t
+            WHERE status = 'running'
+              AND NOT EXISTS (
+                SELECT
+                FROM games.t_pairings p
+                WHERE p.tournament_id = t.id
+                  AND p.round = t.current_round
+                  AND p.black IS NOT NULL
+                  AND p.result IS NULL
+              )
+            ",
+        )
+        .fetch_all(&pool)
+        .await;
+        let due = match due {
+            Ok(due) => due,
+            Err(err) => {
+                error!("Error getting tournaments to pair {err}");
+                continue;
+            }
+        };
+
+        for id in due {
+            // Errors are logged, the tournament is tried again later
+            let _ = advance(&pool, &hub, id).await;
+        }
+    }
+}
+
+async fn advance(pool: &PgPool, hub: &Hub, id: i64) -> Result<(), StatusCode> {
+    let mut trx = pool.begin().await.map_err(|err| {
+        error!("Error starting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let starts = next_round(&mut trx, id).await?;
+
+    trx.commit().await.map_err(|err| {
+        error!("Error commiting transaction {err}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    announce(hub, starts);
+    Ok(())
+}
@@ -0,0 +1,317 @@
+//! Pairing rules and standings, over players given by their seed: their
+//! index when sorted by rating
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod test;
+
+/// Partial pairings tried by the Swiss backtracking before giving up on
+/// avoiding rematches
+const SEARCH_BUDGET: usize = 100_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Format {
+    RoundRobin,
+    Swiss,
+}
+
+impl Format {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Format::RoundRobin => "round_robin",
+            Format::Swiss => "swiss",
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        match text {
+            "round_robin" => Some(Format::RoundRobin),
+            "swiss" => Some(Format::Swiss),
+            _ => None,
+        }
+    }
+
+    /// A Swiss bye is worth a win, in a round robin the player just rests
+    fn bye_points(&self) -> f64 {
+        match self {
+            Format::RoundRobin => 0.0,
+            Format::Swiss => 1.0,
+        }
+    }
+}
+
+/// A game between two seeds, or a bye when there is no black
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Pairing {
+    pub(crate) white: usize,
+    pub(crate) black: Option<usize>,
+    /// PGN result once the game is over
+    pub(crate) result: Option<String>,
+}
+
+impl Pairing {
+    fn new(white: usize, black: Option<usize>) -> Self {
+        Self {
+            white,
+            black,
+            result: None,
+        }
+    }
+}
+
+/// Rounds of a round robin
+pub(crate) fn round_robin_rounds(players: usize) -> usize {
+    (players + players % 2).saturating_sub(1)
+}
+
+/// Pairings of `round`, counting from 1, in the Berger tables for
+/// `players`. With an odd number of players the one meeting the missing
+/// last seed has a bye.
+pub(crate) fn berger(players: usize, round: usize) -> Vec<Pairing> {
+    let n = players + players % 2;
+    if n < 2 || round == 0 || round >= n {
+        return Vec::new();
+    }
+
+    // Seeds count from 1 in the tables, all but the last one rotate
+    let wrap = |seed: isize| ((seed - 1).rem_euclid(n as isize - 1) + 1) as usize;
+    let rotating = if round % 2 == 1 {
+        round.div_ceil(2)
+    } else {
+        round / 2 + n / 2
+    };
+
+    let mut boards = vec![if round % 2 == 1 {
+        (rotating, n)
+    } else {
+        (n, rotating)
+    }];
+    for k in 1..n / 2 {
+        let k = k as isize;
+        boards.push((wrap(rotating as isize + k), wrap(rotating as isize - k)));
+    }
+
+    boards
+        .into_iter()
+        .map(|(white, black)| match (white > players, black > players) {
+            (true, _) => Pairing::new(black - 1, None),
+            (_, true) => Pairing::new(white - 1, None),
+            _ => Pairing::new(white - 1, Some(black - 1)),
+        })
+        .collect()
+}
+
+/// What a player did in the games played so far
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Record {
+    pub(crate) points: f64,
+    /// Opponents with the points scored against each, unfinished games
+    /// counting nothing yet
+    pub(crate) games: Vec<(usize, Option<f64>)>,
+    /// Whites minus blacks
+    pub(crate) colour_balance: i32,
+    /// Whether the last game was with white
+    pub(crate) last_white: Option<bool>,
+    pub(crate) byes: usize,
+}
+
+/// Records of `players` seeds after `pairings`
+pub(crate) fn records(players: usize, pairings: &[Pairing], format: Format) -> Vec<Record> {
+    let mut records = vec![Record::default(); players];
+    for pairing in pairings {
+        let Some(black) = pairing.black else {
+            let record = &mut records[pairing.white];
+            record.points += format.bye_points();
+            record.byes += 1;
+            continue;
+        };
+
+        let white_score = match pairing.result.as_deref() {
+            Some("1-0") => Some(1.0),
+            Some("0-1") => Some(0.0),
+            Some("1/2-1/2") => Some(0.5),
+            _ => None,
+        };
+        for (seed, opponent, white, score) in [
+            (pairing.white, black, true, white_score),
+            (
+                black,
+                pairing.white,
+                false,
+                white_score.map(|score| 1.0 - score),
+            ),
+        ] {
+            let record = &mut records[seed];
+            record.points += score.unwrap_or(0.0);
+            record.games.push((opponent, score));
+            record.colour_balance += if white { 1 } else { -1 };
+            record.last_white = Some(white);
+        }
+    }
+    records
+}
+
+/// Swiss pairings for the players still in, Dutch style: players are
+/// ranked by points then seed, each score group is split in halves that
+/// meet each other, and rematches are avoided by trying the next opponents
+/// in turn, floating players down to the next group when needed.
+pub(crate) fn swiss(records: &[Record], active: &[bool]) -> Vec<Pairing> {
+    let mut ranked: Vec<usize> = (0..records.len()).filter(|seed| active[*seed]).collect();
+    ranked.sort_by(|a, b| {
+        records[*b]
+            .points
+            .total_cmp(&records[*a].points)
+            .then(a.cmp(b))
+    });
+
+    // The lowest ranked player without a bye yet sits out
+    let mut bye = None;
+    if ranked.len() % 2 == 1 {
+        let index = ranked
+            .iter()
+            .rposition(|seed| records[*seed].byes == 0)
+            .unwrap_or(ranked.len() - 1);
+        bye = Some(ranked.remove(index));
+    }
+
+    let mut pairs = Vec::with_capacity(ranked.len() / 2);
+    let mut budget = SEARCH_BUDGET;
+    if !dutch(&ranked, records, false, &mut pairs, &mut budget) {
+        // Too few players left to avoid meeting someone again
+        pairs.clear();
+        budget = usize::MAX;
+        dutch(&ranked, records, true, &mut pairs, &mut budget);
+    }
+
+    let mut pairings: Vec<Pairing> = pairs
+        .into_iter()
+        .enumerate()
+        .map(|(board, (a, b))| {
+            let (white, black) = colours(a, b, board, records);
+            Pairing::new(white, Some(black))
+        })
+        .collect();
+    if let Some(seed) = bye {
+        pairings.push(Pairing::new(seed, None));
+    }
+    pairings
+}
+
+fn dutch(
+    ranked: &[usize],
+    records: &[Record],
+    rematches: bool,
+    pairs: &mut Vec<(usize, usize)>,
+    budget: &mut usize,
+) -> bool {
+    let Some((&first, rest)) = ranked.split_first() else {
+        return true;
+    };
+    if *budget == 0 {
+        return false;
+    }
+    *budget -= 1;
+
+    // The top half of the score group meets the bottom half in order
+    let group = ranked
+        .iter()
+        .take_while(|seed| records[**seed].points == records[first].points)
+        .count();
+    let half = (group / 2).max(1);
+    let candidates = (half..group)
+        .chain((1..half).rev())
+        .chain(group..ranked.len());
+
+    for candidate in candidates {
+        let opponent = ranked[candidate];
+        if !rematches
+            && records[first]
+                .games
+                .iter()
+                .any(|(played, _)| *played == opponent)
+        {
+            continue;
+        }
+
+        let remaining: Vec<usize> = rest
+            .iter()
+            .copied()
+            .filter(|seed| *seed != opponent)
+            .collect();
+        pairs.push((first, opponent));
+        if dutch(&remaining, records, rematches, pairs, budget) {
+            return true;
+        }
+        pairs.pop();
+    }
+    false
+}
+
+/// White and black for `a`, ranked above `b`: whoever had fewer whites,
+/// then whoever had black last, then alternating down the boards
+fn colours(a: usize, b: usize, board: usize, records: &[Record]) -> (usize, usize) {
+    let (ra, rb) = (&records[a], &records[b]);
+    let a_white = match ra.colour_balance.cmp(&rb.colour_balance) {
+        Ordering::Less => true,
+        Ordering::Greater => false,
+        Ordering::Equal => match (ra.last_white, rb.last_white) {
+            (Some(false), _) => true,
+            (Some(true), _) => false,
+            (None, Some(last)) => last,
+            (None, None) => board % 2 == 0,
+        },
+    };
+    if a_white {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Place of a seed in the standings
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Standing {
+    pub(crate) seed: usize,
+    pub(crate) points: f64,
+    /// Points of the opponents met
+    pub(crate) buchholz: f64,
+    /// Points of the opponents beaten, and half those of the ones drawn
+    pub(crate) sonneborn_berger: f64,
+}
+
+/// Standings ordered by points, Buchholz, Sonneborn-Berger and seed
+pub(crate) fn standings(records: &[Record]) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = records
+        .iter()
+        .enumerate()
+        .map(|(seed, record)| {
+            let mut buchholz = 0.0;
+            let mut sonneborn_berger = 0.0;
+            for (opponent, score) in &record.games {
+                let Some(score) = score else { continue };
+                let points = records[*opponent].points;
+                buchholz += points;
+                sonneborn_berger += score * points;
+            }
+            Standing {
+                seed,
+                points: record.points,
+                buchholz,
+                sonneborn_berger,
+            }
+        })
+        .collect();
+
+    standings.sort_by(|a, b| {
+        b.points
+            .total_cmp(&a.points)
+            .then(b.buchholz.total_cmp(&a.buchholz))
+            .then(b.sonneborn_berger.total_cmp(&a.sonneborn_berger))
+            .then(a.seed.cmp(&b.seed))
+    });
+    standings
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use super::*;
+
+fn game(white: usize, black: usize, result: &str) -> Pairing {
+    Pairing {
+        white,
+        black: Some(black),
+        result: Some(result.to_string()),
+    }
+}
+
+fn boards(pairings: &[Pairing]) -> Vec<(usize, Option<usize>)> {
+    pairings.iter().map(|p| (p.white, p.black)).collect()
+}
+
+#[test]
+fn follows_the_berger_tables() {
+    // Seeds from 0, the published tables count from 1
+    let table = [
+        vec![(0, Some(5)), (1, Some(4)), (2, Some(3))],
+        vec![(5, Some(3)), (4, Some(2)), (0, Some(1))],
+        vec![(1, Some(5)), (2, Some(0)), (3, Some(4))],
+        vec![(5, Some(4)), (0, Some(3)), (1, Some(2))],
+        vec![(2, Some(5)), (3, Some(1)), (4, Some(0))],
+    ];
+
+    assert_eq!(round_robin_rounds(6), 5);
+    for (round, expected) in table.iter().enumerate() {
+        assert_eq!(&boards(&berger(6, round + 1)), expected);
+    }
+    assert!(berger(6, 6).is_empty());
+}
+
+#[test]
+fn everybody_meets_once() {
+    for players in 2..=11 {
+        let mut met = HashSet::new();
+        let mut byes = vec![0; players];
+        for round in 1..=round_robin_rounds(players) {
+            let pairings = berger(players, round);
+            let mut seen = HashSet::new();
+            for pairing in pairings {
+                assert!(seen.insert(pairing.white));
+                match pairing.black {
+                    Some(black) => {
+                        assert!(seen.insert(black));
+                        let pair = (pairing.white.min(black), pairing.white.max(black));
+                        assert!(met.insert(pair), "{players} players meet twice");
+                    }
+                    None => byes[pairing.white] += 1,
+                }
+            }
+            assert_eq!(seen.len(), players);
+        }
+
+        assert_eq!(met.len(), players * (players - 1) / 2);
+        let expected_byes = players % 2;
+        assert!(byes.iter().all(|byes| *byes == expected_byes));
+    }
+}
+
+#[test]
+fn swiss_first_round_splits_the_field() {
+    let records = vec![Record::default(); 8];
+    let pairings = swiss(&records, &[true; 8]);
+
+    assert_eq!(
+        boards(&pairings),
+        [(0, Some(4)), (5, Some(1)), (2, Some(6)), (7, Some(3))]
+    );
+}
+
+#[test]
+fn swiss_avoids_rematches_and_alternates_colours() {
+    let played = [
+        game(0, 4, "1-0"),
+        game(5, 1, "0-1"),
+        game(2, 6, "1-0"),
+        game(7, 3, "1/2-1/2"),
+    ];
+    let records = records(8, &played, Format::Swiss);
+    let pairings = swiss(&records, &[true; 8]);
+
+    let mut met: HashSet<(usize, usize)> =
+        played.iter().map(|p| (p.white, p.black.unwrap())).collect();
+    for pairing in &pairings {
+        let (white, black) = (pairing.white, pairing.black.unwrap());
+        assert!(met.insert((white, black)) && !met.contains(&(black, white)));
+        // Nobody gets the same colour twice in a row here
+        assert_eq!(records[white].last_white, Some(false));
+        assert_eq!(records[black].last_white, Some(true));
+    }
+
+    // Three players won: the top two meet, the third floats down
+    let top = &pairings[0];
+    assert_eq!((top.white, top.black), (1, Some(0)));
+}
+
+#[test]
+fn swiss_byes_go_to_the_lowest_ranked_without_one() {
+    let played = [
+        game(0, 3, "1-0"),
+        game(4, 1, "0-1"),
+        Pairing {
+            white: 2,
+            black: None,
+            result: None,
+        },
+    ];
+    let records = records(5, &played, Format::Swiss);
+    assert_eq!(records[2].points, 1.0);
+
+    let pairings = swiss(&records, &[true; 5]);
+    let bye = pairings.iter().find(|p| p.black.is_none()).unwrap();
+    assert_eq!(bye.white, 4);
+    assert_eq!(pairings.len(), 3);
+}
+
+#[test]
+fn withdrawn_players_are_not_paired() {
+    let records = vec![Record::default(); 4];
+    let pairings = swiss(&records, &[true, false, true, true]);
+
+    assert!(pairings.iter().all(|p| p.white != 1 && p.black != Some(1)));
+    assert_eq!(pairings.iter().filter(|p| p.black.is_none()).count(), 1);
+}
+
+#[test]
+fn swiss_allows_rematches_when_nothing_else_is_left() {
+    let played = [game(0, 1, "1-0")];
+    let records = records(2, &played, Format::Swiss);
+
+    assert_eq!(boards(&swiss(&records, &[true; 2])), [(1, Some(0))]);
+}
+
+#[test]
+fn round_robin_byes_are_worth_nothing() {
+    let bye = Pairing {
+        white: 0,
+        black: None,
+        result: None,
+    };
+
+    let byes = [bye];
+    assert_eq!(records(1, &byes, Format::RoundRobin)[0].points, 0.0);
+    assert_eq!(records(1, &byes, Format::Swiss)[0].points, 1.0);
+}
+
+#[test]
+fn standings_break_ties() {
+    // 0 and 1 both score 2: 0 beat the strong 2, 1 beat the weak 3
+    let played = [
+        game(0, 2, "1-0"),
+        game(1, 3, "1-0"),
+        game(0, 3, "1/2-1/2"),
+        game(1, 2, "1/2-1/2"),
+        game(2, 3, "1-0"),
+        game(0, 1, "1/2-1/2"),
+    ];
+    let standings = standings(&records(4, &played, Format::RoundRobin));
+
+    let order: Vec<usize> = standings.iter().map(|s| s.seed).collect();
+    assert_eq!(order, [0, 1, 2, 3]);
+    assert_eq!(standings[0].points, 2.0);
+    assert_eq!(standings[1].points, 2.0);
+    assert_eq!(standings[0].buchholz, 2.0 + 1.5 + 0.5);
+    assert_eq!(standings[0].sonneborn_berger, 1.5 + 0.25 + 1.0);
+    assert_eq!(standings[1].sonneborn_berger, 0.5 + 0.75 + 1.0);
+}
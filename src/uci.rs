@@ -0,0 +1,201 @@
+//! External engines driven over the Universal Chess Interface, kept running
+//! in a pool between searches
+
+use std::{io, process::Stdio, str::FromStr, sync::Mutex, time::Duration};
+
+use chess::{Board, ChessMove};
+use serde::Serialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    process::{Child, ChildStdin, ChildStdout, Command},
+    sync::Semaphore,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Engine's opinion of a position, from the side to move's point of view
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Score {
+    /// Centipawns
+    Cp(i32),
+    /// Moves until mate, negative when being mated
+    Mate(i32),
+}
+
+/// Result of a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Search {
+    pub(crate) best: ChessMove,
+    /// Score of the deepest iteration reported
+    pub(crate) score: Option<Score>,
+    /// Expected continuation, starting with the best move
+    pub(crate) pv: Vec<ChessMove>,
+}
+
+/// A running engine process
+struct Engine {
+    // Killed when dropped
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Engine {
+    async fn start(path: &str) -> io::Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+
+        let mut engine = Self {
+            _child: child,
+            stdin,
+            stdout,
+        };
+        engine.send("uci").await?;
+        engine.wait_for("uciok").await?;
+        engine.send("isready").await?;
+        engine.wait_for("readyok").await?;
+
+        Ok(engine)
+    }
+
+    async fn send(&mut self, command: &str) -> io::Result<()> {
+        self.stdin.write_all(command.as_bytes()).await?;
+        self.stdin.write_all(b"\n").await?;
+        self.stdin.flush().await
+    }
+
+    async fn line(&mut self) -> io::Result<String> {
+        self.stdout
+            .next_line()
+            .await?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "engine exited"))
+    }
+
+    async fn wait_for(&mut self, answer: &str) -> io::Result<()> {
+        while self.line().await?.trim() != answer {}
+        Ok(())
+    }
+
+    async fn search(&mut self, board: &Board, depth: u8) -> io::Result<Search> {
+        self.send(&format!("position fen {board}")).await?;
+        self.send(&format!("go depth {depth}")).await?;
+
+        let mut score = None;
+        let mut pv = Vec::new();
+        loop {
+            let line = self.line().await?;
+            if let Some(best) = line.strip_prefix("bestmove") {
+                let best = best
+                    .split_whitespace()
+                    .next()
+                    .and_then(|best| ChessMove::from_str(best).ok())
+                    .filter(|&best| board.legal(best))
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, format!("bad answer {line}"))
+                    })?;
+                if pv.first() != Some(&best) {
+                    pv = vec![best];
+                }
+                return Ok(Search { best, score, pv });
+            }
+
+            if let Some(info) = parse_info(&line) {
+                score = info.score.or(score);
+                if !info.pv.is_empty() {
+                    pv = info.pv;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Info {
+    score: Option<Score>,
+    pv: Vec<ChessMove>,
+}
+
+/// Read the score and principal variation of an `info` line
+fn parse_info(line: &str) -> Option<Info> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("info") {
+        return None;
+    }
+
+    let mut info = Info::default();
+    while let Some(word) = words.next() {
+        match word {
+            "score" => {
+                let kind = words.next();
+                let value = words.next().and_then(|value| value.parse().ok());
+                info.score = match (kind, value) {
+                    (Some("cp"), Some(value)) => Some(Score::Cp(value)),
+                    (Some("mate"), Some(value)) => Some(Score::Mate(value)),
+                    _ => None,
+                };
+            }
+            // The rest of the line is the variation
+            "pv" => {
+                info.pv = words
+                    .by_ref()
+                    .map_while(|cmove| ChessMove::from_str(cmove).ok())
+                    .collect();
+            }
+            // Free text until the end of the line
+            "string" => break,
+            _ => {}
+        }
+    }
+
+    Some(info)
+}
+
+/// Up to `size` processes of the engine at `path`, started on demand and
+/// reused afterwards
+pub(crate) struct Pool {
+    path: String,
+    idle: Mutex<Vec<Engine>>,
+    slots: Semaphore,
+    timeout: Duration,
+}
+
+impl Pool {
+    pub(crate) fn new(path: String, size: usize, timeout: Duration) -> Self {
+        Self {
+            path,
+            idle: Mutex::default(),
+            slots: Semaphore::new(size),
+            timeout,
+        }
+    }
+
+    /// Search `board` to `depth`. Engines that fail or take longer than
+    /// the timeout are killed, the next search starts a new one.
+    pub(crate) async fn search(&self, board: &Board, depth: u8) -> io::Result<Search> {
+        let _slot = self.slots.acquire().await.expect("pool is never closed");
+
+        let idle = self.idle.lock().expect("pool mutex poisoned").pop();
+        let searching = async {
+            let mut engine = match idle {
+                Some(engine) => engine,
+                None => Engine::start(&self.path).await?,
+            };
+            let search = engine.search(board, depth).await?;
+            Ok::<_, io::Error>((engine, search))
+        };
+        let (engine, search) = tokio::time::timeout(self.timeout, searching)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "engine timed out"))??;
+
+        self.idle.lock().expect("pool mutex poisoned").push(engine);
+        Ok(search)
+    }
+}
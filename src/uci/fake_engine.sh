@@ -0,0 +1,17 @@
+#!/bin/sh
+# Stand-in for a UCI engine in tests. Always answers e2e4 and exits when
+# asked to search 99 plies deep.
+while read -r line; do
+  case "$line" in
+    uci) echo "id name fake"; echo "uciok" ;;
+    isready) echo "readyok" ;;
+    "go depth 99") exit 1 ;;
+    go*)
+      echo "info depth 1 score cp 12 pv d2d4"
+      echo "info string thinking about e2e4"
+      echo "info depth 2 seldepth 3 score mate 3 nodes 42 pv e2e4 e7e5 g1f3"
+      echo "bestmove e2e4 ponder e7e5"
+      ;;
+    quit) exit 0 ;;
+  esac
+done
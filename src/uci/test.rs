@@ -0,0 +1,75 @@
+use chess::Square;
+
+use super::*;
+
+const FAKE_ENGINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/uci/fake_engine.sh");
+
+fn pool(size: usize) -> Pool {
+    Pool::new(FAKE_ENGINE.to_string(), size, Duration::from_secs(5))
+}
+
+fn e2e4() -> ChessMove {
+    ChessMove::new(Square::E2, Square::E4, None)
+}
+
+#[test]
+fn parses_info_lines() {
+    assert_eq!(
+        parse_info("info depth 20 score cp -35 nodes 1000 pv e7e5 g1f3"),
+        Some(Info {
+            score: Some(Score::Cp(-35)),
+            pv: vec![
+                ChessMove::new(Square::E7, Square::E5, None),
+                ChessMove::new(Square::G1, Square::F3, None),
+            ],
+        })
+    );
+    assert_eq!(
+        parse_info("info depth 9 score mate -2 upperbound").map(|info| info.score),
+        Some(Some(Score::Mate(-2)))
+    );
+    assert_eq!(parse_info("info string pv e2e4"), Some(Info::default()));
+    assert_eq!(parse_info("bestmove e2e4"), None);
+}
+
+#[tokio::test]
+async fn searches_and_reuses_engines() {
+    let pool = pool(1);
+
+    for _ in 0..2 {
+        let search = pool.search(&Board::default(), 2).await.unwrap();
+        assert_eq!(search.best, e2e4());
+        assert_eq!(search.score, Some(Score::Mate(3)));
+        assert_eq!(search.pv.len(), 3);
+        assert_eq!(pool.idle.lock().unwrap().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn replaces_engines_that_exit() {
+    let pool = pool(2);
+
+    assert!(pool.search(&Board::default(), 99).await.is_err());
+    assert!(pool.idle.lock().unwrap().is_empty());
+
+    assert_eq!(
+        pool.search(&Board::default(), 1).await.unwrap().best,
+        e2e4()
+    );
+}
+
+#[tokio::test]
+async fn rejects_illegal_answers() {
+    // White has no pawn on e2
+    let board = Board::from_str("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+
+    let err = pool(1).search(&board, 1).await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn fails_when_the_engine_is_missing() {
+    let pool = Pool::new("/nonexistent/engine".to_string(), 1, Duration::from_secs(5));
+
+    assert!(pool.search(&Board::default(), 1).await.is_err());
+}
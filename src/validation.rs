@@ -0,0 +1,92 @@
+use serde::Serialize;
+
+use crate::config::RegistrationRules;
+
+#[cfg(test)]
+mod test;
+
+/// Name finished games of deleted accounts are attributed to, which nobody
+/// can register
+pub(crate) const DELETED_USERNAME: &str = "[deleted]";
+
+/// A registration rule the submitted data does not satisfy
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Violation {
+    field: &'static str,
+    rule: &'static str,
+    message: String,
+}
+
+impl Violation {
+    pub(crate) fn new(field: &'static str, rule: &'static str, message: String) -> Self {
+        Self {
+            field,
+            rule,
+            message,
+        }
+    }
+}
+
+/// Check a new account against every rule, returning all the failing ones
+pub(crate) fn registration(
+    rules: &RegistrationRules,
+    username: &str,
+    password: &str,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+
+    let len = username.chars().count();
+    if len < rules.username_min_len || len > rules.username_max_len {
+        violations.push(Violation::new(
+            "username",
+            "length",
+            format!(
+                "must be between {} and {} characters long",
+                rules.username_min_len, rules.username_max_len
+            ),
+        ));
+    }
+
+    if !username.chars().all(|c| rules.username_charset.contains(c)) {
+        violations.push(Violation::new(
+            "username",
+            "charset",
+            "contains characters that are not allowed".to_string(),
+        ));
+    }
+
+    if rules
+        .reserved_usernames
+        .iter()
+        .any(|reserved| reserved.to_lowercase() == username.to_lowercase())
+    {
+        violations.push(Violation::new(
+            "username",
+            "reserved",
+            "is reserved".to_string(),
+        ));
+    }
+
+    violations.extend(self::password(rules, password));
+
+    violations
+}
+
+/// Check a new password, when registering or changing it
+pub(crate) fn password(rules: &RegistrationRules, password: &str) -> Option<Violation> {
+    (password.chars().count() < rules.password_min_len).then(|| {
+        Violation::new(
+            "password",
+            "length",
+            format!(
+                "must be at least {} characters long",
+                rules.password_min_len
+            ),
+        )
+    })
+}
+
+/// Rule reported when the username is already in use
+pub(crate) fn username_taken() -> Violation {
+    Violation::new("username", "unique", "is already taken".to_string())
+}
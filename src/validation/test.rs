@@ -0,0 +1,69 @@
+use std::str::FromStr;
+
+use super::*;
+use crate::config::Charset;
+
+fn rules() -> RegistrationRules {
+    RegistrationRules {
+        username_min_len: 3,
+        username_max_len: 8,
+        username_charset: Charset::from_str("a-z0-9_-").unwrap(),
+        password_min_len: 4,
+        reserved_usernames: vec!["admin".to_string()],
+    }
+}
+
+fn failing(username: &str, password: &str) -> Vec<(&'static str, &'static str)> {
+    registration(&rules(), username, password)
+        .into_iter()
+        .map(|v| (v.field, v.rule))
+        .collect()
+}
+
+#[test]
+fn valid_registration() {
+    assert!(failing("alice", "hunter2").is_empty());
+    assert!(failing("a-b_c9", "1234").is_empty());
+}
+
+#[test]
+fn every_failing_rule_is_reported() {
+    assert_eq!(
+        failing("", "x"),
+        vec![("username", "length"), ("password", "length")]
+    );
+    assert_eq!(failing("a b", "password"), vec![("username", "charset")]);
+    assert_eq!(
+        failing("waytoolongname", "pw"),
+        vec![("username", "length"), ("password", "length")]
+    );
+}
+
+#[test]
+fn reserved_names_ignore_case() {
+    assert_eq!(
+        failing("AdMin", "password"),
+        vec![("username", "charset"), ("username", "reserved")]
+    );
+    assert_eq!(failing("admin", "password"), vec![("username", "reserved")]);
+}
+
+#[test]
+fn changed_passwords_follow_the_same_rule() {
+    assert_eq!(password(&rules(), "1234"), None);
+    assert_eq!(
+        password(&rules(), "pw").map(|v| (v.field, v.rule)),
+        Some(("password", "length"))
+    );
+}
+
+#[test]
+fn charset_parsing() {
+    let charset = Charset::from_str("-a-c_").unwrap();
+    assert!(charset.contains('-'));
+    assert!(charset.contains('b'));
+    assert!(charset.contains('_'));
+    assert!(!charset.contains('d'));
+    assert!(Charset::from_str("z-a").is_err());
+    assert!(Charset::from_str("").is_err());
+}